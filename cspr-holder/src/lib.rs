@@ -12,17 +12,44 @@ use alloc::{
 use contract::{
     contract_api::{
         account,
-        runtime::{self},
+        runtime::{self, blake2b},
         storage::{self, create_contract_package_at_hash},
         system::{self, transfer_from_purse_to_purse, transfer_from_purse_to_account}
     },
     unwrap_or_revert::UnwrapOrRevert
 };
-use types::{ApiError, CLType, CLTyped, CLValue, ContractHash, Group, Key, Parameter, PublicKey, RuntimeArgs, U256, U512, URef, account::AccountHash, bytesrepr::{FromBytes, ToBytes}, contracts::{EntryPoint, EntryPointAccess, EntryPointType, EntryPoints, NamedKeys}, runtime_args, system::CallStackElement};
+use types::{ApiError, CLType, CLTyped, CLValue, ContractHash, ContractPackageHash, Group, Key, Parameter, PublicKey, RuntimeArgs, U256, U512, URef, account::AccountHash, bytesrepr::{FromBytes, ToBytes}, contracts::{EntryPoint, EntryPointAccess, EntryPointType, EntryPoints, NamedKeys}, runtime_args, system::CallStackElement};
 
 pub enum Error {
     DepositAmountTooSmall = 0,
     NoAccessRights = 1,
+    UnlockAlreadyProcessed = 2,
+    NotASigner = 3,
+    AlreadyApproved = 4,
+    InvalidThreshold = 5,
+    SignerExists = 6,
+    SignerNotFound = 7,
+    NoPendingGovernance = 8,
+    GovernanceDelayNotPassed = 9,
+    Paused = 10,
+    UnlockAmountTooSmall = 11,
+    UnlockAmountTooLarge = 12,
+    DailyUnlockCapExceeded = 13,
+    UnlockNotQueued = 14,
+    UnlockTimelockNotPassed = 15,
+    AccountingMismatch = 16,
+    UnsupportedUnlockTarget = 17,
+    UnlockPurseNotAddable = 18,
+    WcsprAlreadySet = 19,
+    InvalidWcsprDecimals = 20,
+    UnlockDisabledInWrappedMode = 21,
+    WcsprNotSet = 22,
+    AmountOverflow = 23,
+    DepositNotPending = 24,
+    RefundsDisabled = 25,
+    RefundTimeoutNotPassed = 26,
+    LockDisabledInWrappedMode = 27,
+    DepositsPending = 28,
 }
 
 impl From<Error> for ApiError {
@@ -31,33 +58,450 @@ impl From<Error> for ApiError {
     }
 }
 
+/// Delay before a proposed governance can be applied: 2 days, block time being in milliseconds.
+const GOVERNANCE_DELAY: u64 = 172_800_000;
+
+/// Window over which the daily unlock cap refills completely: 24 hours, in milliseconds.
+const UNLOCK_CAP_WINDOW: u64 = 86_400_000;
+
+/// Delay before a large unlock can be executed, leaving governance time to cancel it: 24 hours.
+const LARGE_UNLOCK_DELAY: u64 = 86_400_000;
+
+/// WCSPR is denominated in motes, so amounts are only ever widened or narrowed, never scaled.
+const WCSPR_DECIMALS: u8 = 9;
+
+pub enum Event {
+    Paused { account: Key },
+    Unpaused { account: Key },
+    UnlockQueued { tx_id: String, target: Key, amount: U512, release_time: u64 },
+    UnlockCancelled { tx_id: String },
+    DepositCompleted { deposit_id: u64 },
+    DepositRefunded { deposit_id: u64, depositor: Key, amount: U512 },
+}
+
+impl Event {
+    /// Flattens the event into the string map stored in the `cspr_holder_events` dictionary.
+    fn into_map(self) -> BTreeMap<String, String> {
+        let mut event = BTreeMap::new();
+        match self {
+            Event::Paused { account } => {
+                event.insert("event_type".to_string(), "paused".to_string());
+                event.insert("account".to_string(), account.to_formatted_string());
+            }
+            Event::Unpaused { account } => {
+                event.insert("event_type".to_string(), "unpaused".to_string());
+                event.insert("account".to_string(), account.to_formatted_string());
+            }
+            Event::UnlockQueued { tx_id, target, amount, release_time } => {
+                event.insert("event_type".to_string(), "unlock_queued".to_string());
+                event.insert("tx_id".to_string(), tx_id);
+                event.insert("target".to_string(), target.to_formatted_string());
+                event.insert("amount".to_string(), amount.to_string());
+                event.insert("release_time".to_string(), release_time.to_string());
+            }
+            Event::UnlockCancelled { tx_id } => {
+                event.insert("event_type".to_string(), "unlock_cancelled".to_string());
+                event.insert("tx_id".to_string(), tx_id);
+            }
+            Event::DepositCompleted { deposit_id } => {
+                event.insert("event_type".to_string(), "deposit_completed".to_string());
+                event.insert("deposit_id".to_string(), deposit_id.to_string());
+            }
+            Event::DepositRefunded { deposit_id, depositor, amount } => {
+                event.insert("event_type".to_string(), "deposit_refunded".to_string());
+                event.insert("deposit_id".to_string(), deposit_id.to_string());
+                event.insert("depositor".to_string(), depositor.to_formatted_string());
+                event.insert("amount".to_string(), amount.to_string());
+            }
+        }
+        event
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn lock() {
     let src_purse: URef = runtime::get_named_arg("src_purse");
     let amount: U512 = runtime::get_named_arg("amount");
-    if (amount <= U512::from(0)) {
-        runtime::revert(Error::DepositAmountTooSmall);
+    let to_chain_id: U256 = runtime::get_named_arg("to_chain_id");
+    let to_address: String = runtime::get_named_arg("to_address");
+    _lock(get_caller(), src_purse, amount, to_chain_id, to_address);
+}
+
+/// Locks on behalf of `depositor`, so deposits made through the router are credited and refunded
+/// to the user rather than to the router itself.
+#[no_mangle]
+pub extern "C" fn lock_for() {
+    if (Some(get_caller()) != get_key::<Option<Key>>("cspr_holder_data", "router")) {
+        runtime::revert(Error::NoAccessRights);
     }
-    let contract_purse_key = runtime::get_key("contract_purse").unwrap_or_revert();
-    let contract_purse = *contract_purse_key.as_uref().unwrap_or_revert();
-    
-    transfer_from_purse_to_purse(src_purse, contract_purse, amount, None)
-        .unwrap_or_revert();
-    
+    let src_purse: URef = runtime::get_named_arg("src_purse");
+    let amount: U512 = runtime::get_named_arg("amount");
+    let to_chain_id: U256 = runtime::get_named_arg("to_chain_id");
+    let to_address: String = runtime::get_named_arg("to_address");
+    let depositor: Key = runtime::get_named_arg("depositor");
+    _lock(depositor, src_purse, amount, to_chain_id, to_address);
 }
 
 #[no_mangle]
-pub extern "C" fn unlock() {
+pub extern "C" fn set_router() {
     _authorization_check();
-    let target_pubkey: PublicKey = runtime::get_named_arg("target_pubkey");
+    let router: Option<Key> = runtime::get_named_arg("router");
+    set_key("cspr_holder_data", "router", router);
+}
+
+#[no_mangle]
+pub extern "C" fn router() {
+    let val: Option<Key> = get_key("cspr_holder_data", "router");
+    ret(val)
+}
+
+/// Locks CSPR from `src_purse` and mints the same amount of WCSPR to the caller.
+#[no_mangle]
+pub extern "C" fn wrap() {
+    let src_purse: URef = runtime::get_named_arg("src_purse");
     let amount: U512 = runtime::get_named_arg("amount");
+    let wcspr = _wcspr().unwrap_or_revert_with(Error::WcsprNotSet);
+    let owner = get_caller();
+    _receive_deposit(owner, src_purse, amount);
+    runtime::call_contract::<()>(
+        wcspr,
+        "mint",
+        runtime_args! {
+            "owner" => owner,
+            "amount" => u512_to_u256(amount)
+        }
+    );
+}
 
-    let contract_purse_key = runtime::get_key("contract_purse").unwrap_or_revert();
-    let contract_purse = *contract_purse_key.as_uref().unwrap_or_revert();
+/// Burns the caller's WCSPR and releases the same amount of CSPR back to it. The burn goes through
+/// `burn_from`, so the caller has to approve the holder for `amount` first.
+#[no_mangle]
+pub extern "C" fn redeem() {
+    _when_not_paused();
+    let amount: U512 = runtime::get_named_arg("amount");
+    let wcspr = _wcspr().unwrap_or_revert_with(Error::WcsprNotSet);
+    let owner = get_caller();
+    runtime::call_contract::<()>(
+        wcspr,
+        "burn_from",
+        runtime_args! {
+            "owner" => owner,
+            "amount" => u512_to_u256(amount)
+        }
+    );
+    _release(owner, amount);
+}
 
-    transfer_from_purse_to_account(contract_purse, target_pubkey.to_account_hash(), amount, None)
-        .unwrap_or_revert();
+/// Switches the holder to wrapped mode for good: CSPR only comes in through `wrap` and leaves through
+/// `redeem`, bridge locks and unlocks are disabled. The token needs this contract as a minter, and every
+/// deposit has to be completed or refunded first.
+#[no_mangle]
+pub extern "C" fn set_wcspr() {
+    _authorization_check();
+    let token: Key = runtime::get_named_arg("token");
+    if (_wcspr().is_some()) {
+        runtime::revert(Error::WcsprAlreadySet);
+    }
+    // A pending deposit could still be refunded, paying out CSPR that no WCSPR is burnt for.
+    if (get_key::<u64>("cspr_holder_data", "pending_deposit_count") > 0) {
+        runtime::revert(Error::DepositsPending);
+    }
+    let wcspr = ContractHash::new(token.into_hash().unwrap_or_revert());
+    if (runtime::call_contract::<u8>(wcspr, "decimals", runtime_args! {}) != WCSPR_DECIMALS) {
+        runtime::revert(Error::InvalidWcsprDecimals);
+    }
+    // Every mote already in the purse must be backed by WCSPR from the start.
+    let total_supply: U256 = runtime::call_contract(wcspr, "total_supply", runtime_args! {});
+    if (total_supply != u512_to_u256(system::get_purse_balance(_contract_purse()).unwrap_or_revert())) {
+        runtime::revert(Error::AccountingMismatch);
+    }
+    set_key("cspr_holder_data", "wcspr", Some(token));
+}
+
+#[no_mangle]
+pub extern "C" fn wcspr() {
+    let val: Option<Key> = get_key("cspr_holder_data", "wcspr");
+    ret(val)
+}
+
+/// Marks a deposit as bridged, after which it can no longer be refunded.
+#[no_mangle]
+pub extern "C" fn complete_deposit() {
+    _authorization_check();
+    let deposit_id: u64 = runtime::get_named_arg("deposit_id");
+    _take_pending_deposit(deposit_id, "completed");
+    emit(Event::DepositCompleted { deposit_id });
+}
+
+/// Sends a deposit the bridge never picked up back to its depositor once `refund_timeout` has passed.
+/// A refund undoes the lock instead of unlocking bridged CSPR, so it takes the deposit off the locked
+/// totals, leaves `total_unlocked` alone and is not held to the unlock limits or the daily unlock cap.
+#[no_mangle]
+pub extern "C" fn refund() {
+    _when_not_paused();
+    let deposit_id: u64 = runtime::get_named_arg("deposit_id");
+    let refund_timeout = get_key::<Option<u64>>("cspr_holder_data", "refund_timeout")
+        .unwrap_or_revert_with(Error::RefundsDisabled);
+    let (_, _, timestamp) = _pending_deposit(deposit_id);
+    if (u64::from(runtime::get_blocktime()) < timestamp.saturating_add(refund_timeout)) {
+        runtime::revert(Error::RefundTimeoutNotPassed);
+    }
+    let (depositor, amount, _) = _take_pending_deposit(deposit_id, "refunded");
+    let total_locked: U512 = get_key("cspr_holder_data", "total_locked");
+    set_key("cspr_holder_data", "total_locked", total_locked - amount);
+    let deposited: U512 = get_key("deposits_by_account", &key_to_str(&depositor));
+    set_key("deposits_by_account", &key_to_str(&depositor), deposited - amount);
+    _pay(depositor, amount);
+    emit(Event::DepositRefunded { deposit_id, depositor, amount });
+}
+
+#[no_mangle]
+pub extern "C" fn set_refund_timeout() {
+    _authorization_check();
+    let refund_timeout: Option<u64> = runtime::get_named_arg("refund_timeout");
+    set_key("cspr_holder_data", "refund_timeout", refund_timeout);
+}
+
+#[no_mangle]
+pub extern "C" fn approve_unlock() {
+    _when_not_paused();
+    _when_not_wrapped();
+    let signer = get_caller();
+    if (!get_key::<bool>("signers", &key_to_str(&signer))) {
+        runtime::revert(Error::NotASigner);
+    }
+    let tx_id: String = runtime::get_named_arg("tx_id");
+    let target: Key = runtime::get_named_arg("target");
+    let amount: U512 = runtime::get_named_arg("amount");
+    if (get_key::<bool>("processed_unlocks", &tx_id_key(&tx_id))) {
+        runtime::revert(Error::UnlockAlreadyProcessed);
+    }
+    if (amount < get_key::<U512>("cspr_holder_data", "min_unlock")) {
+        runtime::revert(Error::UnlockAmountTooSmall);
+    }
+    if let Some(max_unlock) = get_key::<Option<U512>>("cspr_holder_data", "max_unlock") {
+        if (amount > max_unlock) {
+            runtime::revert(Error::UnlockAmountTooLarge);
+        }
+    }
+    // Approvals only add up when the signers agree on the whole request, not just the tx id.
+    let request_key = unlock_request_key(&tx_id, &target, amount);
+    let mut approvers: Vec<Key> = get_key("unlock_approvals", &request_key);
+    if (approvers.contains(&signer)) {
+        runtime::revert(Error::AlreadyApproved);
+    }
+    approvers.push(signer);
+    // Approvals from signers removed in the meantime no longer count.
+    let approvals = approvers
+        .iter()
+        .filter(|approver| get_key::<bool>("signers", &key_to_str(approver)))
+        .count() as u32;
+    set_key("unlock_approvals", &request_key, approvers);
+    if (approvals >= get_key::<u32>("cspr_holder_data", "threshold")) {
+        // Each source-chain transaction can only release CSPR once, queued or not.
+        set_key("processed_unlocks", &tx_id_key(&tx_id), true);
+        match get_key::<Option<U512>>("cspr_holder_data", "large_unlock_threshold") {
+            Some(large_unlock_threshold) if amount >= large_unlock_threshold => {
+                let release_time = u64::from(runtime::get_blocktime()) + LARGE_UNLOCK_DELAY;
+                set_key("queued_unlocks", &tx_id_key(&tx_id), Some((target, amount, release_time)));
+                emit(Event::UnlockQueued { tx_id, target, amount, release_time });
+            }
+            _ => _unlock(target, amount),
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn execute_unlock() {
+    _when_not_paused();
+    _when_not_wrapped();
+    let tx_id: String = runtime::get_named_arg("tx_id");
+    let (target, amount, release_time) = get_key::<Option<(Key, U512, u64)>>("queued_unlocks", &tx_id_key(&tx_id))
+        .unwrap_or_revert_with(Error::UnlockNotQueued);
+    if (u64::from(runtime::get_blocktime()) < release_time) {
+        runtime::revert(Error::UnlockTimelockNotPassed);
+    }
+    set_key("queued_unlocks", &tx_id_key(&tx_id), None::<(Key, U512, u64)>);
+    _unlock(target, amount);
+}
+
+/// Drops a queued unlock; the tx id stays processed so it can't be approved again.
+#[no_mangle]
+pub extern "C" fn cancel_unlock() {
+    _authorization_check();
+    let tx_id: String = runtime::get_named_arg("tx_id");
+    if (get_key::<Option<(Key, U512, u64)>>("queued_unlocks", &tx_id_key(&tx_id)).is_none()) {
+        runtime::revert(Error::UnlockNotQueued);
+    }
+    set_key("queued_unlocks", &tx_id_key(&tx_id), None::<(Key, U512, u64)>);
+    emit(Event::UnlockCancelled { tx_id });
+}
+
+#[no_mangle]
+pub extern "C" fn set_unlock_limits() {
+    _authorization_check();
+    let min_unlock: U512 = runtime::get_named_arg("min_unlock");
+    let max_unlock: Option<U512> = runtime::get_named_arg("max_unlock");
+    let daily_unlock_cap: Option<U512> = runtime::get_named_arg("daily_unlock_cap");
+    let large_unlock_threshold: Option<U512> = runtime::get_named_arg("large_unlock_threshold");
+    set_key("cspr_holder_data", "min_unlock", min_unlock);
+    set_key("cspr_holder_data", "max_unlock", max_unlock);
+    set_key("cspr_holder_data", "daily_unlock_cap", daily_unlock_cap);
+    set_key("cspr_holder_data", "large_unlock_threshold", large_unlock_threshold);
+    // The cap starts out with its whole window available.
+    if let Some(daily_unlock_cap) = daily_unlock_cap {
+        set_key("cspr_holder_data", "unlock_cap_bucket", (u64::from(runtime::get_blocktime()), daily_unlock_cap));
+    }
+}
 
+#[no_mangle]
+pub extern "C" fn is_processed() {
+    let tx_id: String = runtime::get_named_arg("tx_id");
+    let val: bool = get_key("processed_unlocks", &tx_id_key(&tx_id));
+    ret(val)
+}
+
+/// Hands out a read-only copy of the purse so balances can be checked without going through the contract.
+#[no_mangle]
+pub extern "C" fn contract_purse() {
+    ret(_contract_purse().into_read())
+}
+
+#[no_mangle]
+pub extern "C" fn purse_balance() {
+    ret(system::get_purse_balance(_contract_purse()).unwrap_or_revert())
+}
+
+#[no_mangle]
+pub extern "C" fn total_locked() {
+    let val: U512 = get_key("cspr_holder_data", "total_locked");
+    ret(val)
+}
+
+#[no_mangle]
+pub extern "C" fn total_unlocked() {
+    let val: U512 = get_key("cspr_holder_data", "total_unlocked");
+    ret(val)
+}
+
+#[no_mangle]
+pub extern "C" fn deposits_of() {
+    let account: Key = runtime::get_named_arg("account");
+    let val: U512 = get_key("deposits_by_account", &key_to_str(&account));
+    ret(val)
+}
+
+/// Reverts unless the purse holds exactly what was locked minus what was released,
+/// and, in wrapped mode, exactly the WCSPR total supply.
+#[no_mangle]
+pub extern "C" fn reconcile() {
+    let total_locked: U512 = get_key("cspr_holder_data", "total_locked");
+    let total_unlocked: U512 = get_key("cspr_holder_data", "total_unlocked");
+    let balance = system::get_purse_balance(_contract_purse()).unwrap_or_revert();
+    if (total_locked.checked_sub(total_unlocked) != Some(balance)) {
+        runtime::revert(Error::AccountingMismatch);
+    }
+    if let Some(wcspr) = _wcspr() {
+        let total_supply: U256 = runtime::call_contract(wcspr, "total_supply", runtime_args! {});
+        if (total_supply != u512_to_u256(balance)) {
+            runtime::revert(Error::AccountingMismatch);
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn paused() {
+    let val: bool = get_key("cspr_holder_data", "paused");
+    ret(val)
+}
+
+#[no_mangle]
+pub extern "C" fn pause() {
+    _authorization_check();
+    set_key("cspr_holder_data", "paused", true);
+    emit(Event::Paused { account: get_caller() });
+}
+
+#[no_mangle]
+pub extern "C" fn unpause() {
+    _authorization_check();
+    set_key("cspr_holder_data", "paused", false);
+    emit(Event::Unpaused { account: get_caller() });
+}
+
+#[no_mangle]
+pub extern "C" fn add_signer() {
+    _authorization_check();
+    let signer: Key = runtime::get_named_arg("signer");
+    let signer_key = key_to_str(&signer);
+    if (get_key::<bool>("signers", &signer_key)) {
+        runtime::revert(Error::SignerExists);
+    }
+    set_key("signers", &signer_key, true);
+    let signer_count: u32 = get_key("cspr_holder_data", "signer_count");
+    set_key("cspr_holder_data", "signer_count", signer_count + 1);
+}
+
+#[no_mangle]
+pub extern "C" fn remove_signer() {
+    _authorization_check();
+    let signer: Key = runtime::get_named_arg("signer");
+    let signer_key = key_to_str(&signer);
+    if (!get_key::<bool>("signers", &signer_key)) {
+        runtime::revert(Error::SignerNotFound);
+    }
+    let signer_count: u32 = get_key("cspr_holder_data", "signer_count");
+    if (signer_count - 1 < get_key::<u32>("cspr_holder_data", "threshold")) {
+        runtime::revert(Error::InvalidThreshold);
+    }
+    set_key("signers", &signer_key, false);
+    set_key("cspr_holder_data", "signer_count", signer_count - 1);
+}
+
+#[no_mangle]
+pub extern "C" fn set_threshold() {
+    _authorization_check();
+    let threshold: u32 = runtime::get_named_arg("threshold");
+    if (threshold == 0 || threshold > get_key::<u32>("cspr_holder_data", "signer_count")) {
+        runtime::revert(Error::InvalidThreshold);
+    }
+    set_key("cspr_holder_data", "threshold", threshold);
+}
+
+#[no_mangle]
+pub extern "C" fn governance() {
+    let val: Key = get_governance("cspr_holder_data");
+    ret(val)
+}
+
+#[no_mangle]
+pub extern "C" fn pending_governance() {
+    let val: Option<Key> = get_key("cspr_holder_data", "pending_governance");
+    ret(val)
+}
+
+#[no_mangle]
+pub extern "C" fn propose_governance() {
+    _authorization_check();
+    let new_governance: Key = runtime::get_named_arg("new_governance");
+    let effective_time = u64::from(runtime::get_blocktime()) + GOVERNANCE_DELAY;
+    set_key("cspr_holder_data", "pending_governance", Some(new_governance));
+    set_key("cspr_holder_data", "governance_effective_time", effective_time);
+}
+
+#[no_mangle]
+pub extern "C" fn apply_governance() {
+    let pending_governance = get_key::<Option<Key>>("cspr_holder_data", "pending_governance")
+        .unwrap_or_revert_with(Error::NoPendingGovernance);
+    // The new key has to prove it is usable before it takes over.
+    if (get_caller() != pending_governance) {
+        runtime::revert(Error::NoAccessRights);
+    }
+    if (u64::from(runtime::get_blocktime()) < get_key::<u64>("cspr_holder_data", "governance_effective_time")) {
+        runtime::revert(Error::GovernanceDelayNotPassed);
+    }
+    set_key("cspr_holder_data", "governance", pending_governance);
+    set_key("cspr_holder_data", "pending_governance", None::<Key>);
 }
 
 #[no_mangle]
@@ -70,23 +514,185 @@ pub extern "C" fn call() {
         vec![
             Parameter::new("src_purse", CLType::URef),
             Parameter::new("amount", CLType::U512),
+            Parameter::new("to_chain_id", CLType::U256),
+            Parameter::new("to_address", CLType::String),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "lock_for",
+        vec![
+            Parameter::new("src_purse", CLType::URef),
+            Parameter::new("amount", CLType::U512),
+            Parameter::new("to_chain_id", CLType::U256),
+            Parameter::new("to_address", CLType::String),
+            Parameter::new("depositor", CLType::Key),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "set_router",
+        vec![
+            Parameter::new("router", Option::<Key>::cl_type()),
         ],
         CLType::Unit,
     ));
+    entry_points.add_entry_point(endpoint("router", vec![], Option::<Key>::cl_type()));
     entry_points.add_entry_point(endpoint(
-        "unlock",
+        "approve_unlock",
         vec![
-            Parameter::new("target_pubkey", CLType::PublicKey),
+            Parameter::new("tx_id", CLType::String),
+            Parameter::new("target", CLType::Key),
             Parameter::new("amount", CLType::U512),
         ],
         CLType::Unit,
     ));
+    entry_points.add_entry_point(endpoint(
+        "is_processed",
+        vec![
+            Parameter::new("tx_id", CLType::String),
+        ],
+        CLType::Bool,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "add_signer",
+        vec![
+            Parameter::new("signer", CLType::Key),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "remove_signer",
+        vec![
+            Parameter::new("signer", CLType::Key),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "set_threshold",
+        vec![
+            Parameter::new("threshold", CLType::U32),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "execute_unlock",
+        vec![
+            Parameter::new("tx_id", CLType::String),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "cancel_unlock",
+        vec![
+            Parameter::new("tx_id", CLType::String),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "set_unlock_limits",
+        vec![
+            Parameter::new("min_unlock", CLType::U512),
+            Parameter::new("max_unlock", Option::<U512>::cl_type()),
+            Parameter::new("daily_unlock_cap", Option::<U512>::cl_type()),
+            Parameter::new("large_unlock_threshold", Option::<U512>::cl_type()),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint("contract_purse", vec![], CLType::URef));
+    entry_points.add_entry_point(endpoint("purse_balance", vec![], CLType::U512));
+    entry_points.add_entry_point(endpoint("total_locked", vec![], CLType::U512));
+    entry_points.add_entry_point(endpoint("total_unlocked", vec![], CLType::U512));
+    entry_points.add_entry_point(endpoint(
+        "deposits_of",
+        vec![
+            Parameter::new("account", CLType::Key),
+        ],
+        CLType::U512,
+    ));
+    entry_points.add_entry_point(endpoint("reconcile", vec![], CLType::Unit));
+    entry_points.add_entry_point(endpoint(
+        "wrap",
+        vec![
+            Parameter::new("src_purse", CLType::URef),
+            Parameter::new("amount", CLType::U512),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "redeem",
+        vec![
+            Parameter::new("amount", CLType::U512),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "set_wcspr",
+        vec![
+            Parameter::new("token", CLType::Key),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint("wcspr", vec![], Option::<Key>::cl_type()));
+    entry_points.add_entry_point(endpoint(
+        "complete_deposit",
+        vec![
+            Parameter::new("deposit_id", CLType::U64),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "refund",
+        vec![
+            Parameter::new("deposit_id", CLType::U64),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "set_refund_timeout",
+        vec![
+            Parameter::new("refund_timeout", Option::<u64>::cl_type()),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint("paused", vec![], CLType::Bool));
+    entry_points.add_entry_point(endpoint("pause", vec![], CLType::Unit));
+    entry_points.add_entry_point(endpoint("unpause", vec![], CLType::Unit));
+    entry_points.add_entry_point(endpoint("governance", vec![], CLType::Key));
+    entry_points.add_entry_point(endpoint("pending_governance", vec![], Option::<Key>::cl_type()));
+    entry_points.add_entry_point(endpoint(
+        "propose_governance",
+        vec![
+            Parameter::new("new_governance", CLType::Key),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint("apply_governance", vec![], CLType::Unit));
+
+    let governance: Key = runtime::get_named_arg("governance");
 
     let dictionary_seed_uref = storage::new_dictionary("cspr_holder_data").unwrap_or_revert();
     storage::dictionary_put(
         dictionary_seed_uref,
         "governance",
-        runtime::get_named_arg::<AccountHash>("governance")
+        governance
+    );
+    // Governance starts as the only signer, so a single approval releases funds until more are added.
+    storage::dictionary_put(
+        dictionary_seed_uref,
+        "signer_count",
+        1u32
+    );
+    storage::dictionary_put(
+        dictionary_seed_uref,
+        "threshold",
+        1u32
+    );
+    let signers_seed_uref = storage::new_dictionary("signers").unwrap_or_revert();
+    storage::dictionary_put(
+        signers_seed_uref,
+        &key_to_str(&governance),
+        true
     );
     let mut named_keys = NamedKeys::new();
     named_keys.insert(
@@ -97,6 +703,38 @@ pub extern "C" fn call() {
         "contract_purse".to_string(),
         contract_purse.into()
     );
+    named_keys.insert(
+        "deposits".to_string(),
+        storage::new_dictionary("deposits").unwrap_or_revert().into()
+    );
+    named_keys.insert(
+        "deposits_by_account".to_string(),
+        storage::new_dictionary("deposits_by_account").unwrap_or_revert().into()
+    );
+    named_keys.insert(
+        "pending_deposits".to_string(),
+        storage::new_dictionary("pending_deposits").unwrap_or_revert().into()
+    );
+    named_keys.insert(
+        "processed_unlocks".to_string(),
+        storage::new_dictionary("processed_unlocks").unwrap_or_revert().into()
+    );
+    named_keys.insert(
+        "signers".to_string(),
+        signers_seed_uref.into()
+    );
+    named_keys.insert(
+        "unlock_approvals".to_string(),
+        storage::new_dictionary("unlock_approvals").unwrap_or_revert().into()
+    );
+    named_keys.insert(
+        "queued_unlocks".to_string(),
+        storage::new_dictionary("queued_unlocks").unwrap_or_revert().into()
+    );
+    named_keys.insert(
+        "cspr_holder_events".to_string(),
+        storage::new_dictionary("cspr_holder_events").unwrap_or_revert().into()
+    );
 
     let (contract_package_hash, access_uref) = create_contract_package_at_hash();
     // Add new version to the package.
@@ -105,6 +743,10 @@ pub extern "C" fn call() {
     // Save contract and contract hash in the caller's context.
     runtime::put_key("CSPR_Holder", contract_hash.into());
     runtime::put_key("CSPR_Holder_hash", storage::new_uref(contract_hash).into());
+    // The package hash is the holder's identity towards the WCSPR token.
+    runtime::put_key("CSPR_Holder_package_hash", storage::new_uref(contract_package_hash).into());
+    // Read-only, so the deployer's account can surface the purse balance to off-chain tools.
+    runtime::put_key("CSPR_Holder_purse", contract_purse.into_read().into());
     // Save access_uref
     runtime::put_key("access_uref", access_uref.into());
     // Save contract_hash under the contract's dictionary to be accessed through the contract's endpoints.
@@ -115,6 +757,153 @@ pub extern "C" fn call() {
     );
 }
 
+fn _lock(depositor: Key, src_purse: URef, amount: U512, to_chain_id: U256, to_address: String) {
+    if (_wcspr().is_some()) {
+        runtime::revert(Error::LockDisabledInWrappedMode);
+    }
+    _receive_deposit(depositor, src_purse, amount);
+    // Record the deposit so the MPC signers can mint on the destination chain.
+    let nonce: u64 = get_key("cspr_holder_data", "deposit_count");
+    let mut deposit = BTreeMap::new();
+    deposit.insert("depositor".to_string(), depositor.to_formatted_string());
+    deposit.insert("amount".to_string(), amount.to_string());
+    deposit.insert("to_chain_id".to_string(), to_chain_id.to_string());
+    deposit.insert("to_address".to_string(), to_address);
+    deposit.insert("timestamp".to_string(), u64::from(runtime::get_blocktime()).to_string());
+    deposit.insert("status".to_string(), "pending".to_string());
+    set_key("deposits", &nonce.to_string(), deposit);
+    set_key("cspr_holder_data", "deposit_count", nonce + 1);
+    // Kept typed next to the readable record so a refund never has to parse strings back.
+    set_key("pending_deposits", &nonce.to_string(), Some((depositor, amount, u64::from(runtime::get_blocktime()))));
+    let pending_deposit_count: u64 = get_key("cspr_holder_data", "pending_deposit_count");
+    set_key("cspr_holder_data", "pending_deposit_count", pending_deposit_count + 1);
+}
+
+/// Moves `amount` into the contract purse and credits it to `depositor`, for both locks and wraps.
+fn _receive_deposit(depositor: Key, src_purse: URef, amount: U512) {
+    _when_not_paused();
+    if (amount <= U512::from(0)) {
+        runtime::revert(Error::DepositAmountTooSmall);
+    }
+    transfer_from_purse_to_purse(src_purse, _contract_purse(), amount, None)
+        .unwrap_or_revert();
+    let total_locked: U512 = get_key("cspr_holder_data", "total_locked");
+    set_key("cspr_holder_data", "total_locked", total_locked + amount);
+    let deposited: U512 = get_key("deposits_by_account", &key_to_str(&depositor));
+    set_key("deposits_by_account", &key_to_str(&depositor), deposited + amount);
+}
+
+fn _unlock(target: Key, amount: U512) {
+    if let Some(daily_unlock_cap) = get_key::<Option<U512>>("cspr_holder_data", "daily_unlock_cap") {
+        let available = _available_unlock_cap(daily_unlock_cap);
+        if (amount > available) {
+            runtime::revert(Error::DailyUnlockCapExceeded);
+        }
+        set_key("cspr_holder_data", "unlock_cap_bucket", (u64::from(runtime::get_blocktime()), available - amount));
+    }
+    _release(target, amount);
+}
+
+fn _release(target: Key, amount: U512) {
+    _pay(target, amount);
+    let total_unlocked: U512 = get_key("cspr_holder_data", "total_unlocked");
+    set_key("cspr_holder_data", "total_unlocked", total_unlocked + amount);
+}
+
+/// Pays `target` out of the contract purse: an account's main purse, a purse the caller can add to,
+/// or the purse a contract package hands out through its `receiving_purse` entry point.
+fn _pay(target: Key, amount: U512) {
+    match target {
+        Key::Account(account) => {
+            transfer_from_purse_to_account(_contract_purse(), account, amount, None)
+                .unwrap_or_revert();
+        }
+        Key::URef(purse) => _unlock_to_purse(purse, amount),
+        Key::Hash(package_hash) => {
+            let purse: URef = runtime::call_versioned_contract(
+                ContractPackageHash::new(package_hash),
+                None,
+                "receiving_purse",
+                runtime_args! {}
+            );
+            _unlock_to_purse(purse, amount);
+        }
+        _ => runtime::revert(Error::UnsupportedUnlockTarget),
+    }
+}
+
+fn _unlock_to_purse(purse: URef, amount: U512) {
+    if (!purse.is_addable()) {
+        runtime::revert(Error::UnlockPurseNotAddable);
+    }
+    transfer_from_purse_to_purse(_contract_purse(), purse, amount, None)
+        .unwrap_or_revert();
+}
+
+
+fn _wcspr() -> Option<ContractHash> {
+    get_key::<Option<Key>>("cspr_holder_data", "wcspr")
+        .map(|token| ContractHash::new(token.into_hash().unwrap_or_revert()))
+}
+
+fn u512_to_u256(amount: U512) -> U256 {
+    let mut bytes = [0u8; 64];
+    amount.to_little_endian(&mut bytes);
+    if (bytes[32..].iter().any(|byte| *byte != 0)) {
+        runtime::revert(Error::AmountOverflow);
+    }
+    U256::from_little_endian(&bytes[..32])
+}
+
+/// The depositor, amount and timestamp of a deposit that is still waiting for the bridge.
+fn _pending_deposit(deposit_id: u64) -> (Key, U512, u64) {
+    get_key::<Option<(Key, U512, u64)>>("pending_deposits", &deposit_id.to_string())
+        .unwrap_or_revert_with(Error::DepositNotPending)
+}
+
+/// Closes a pending deposit with its final `status`, returning its depositor, amount and timestamp.
+fn _take_pending_deposit(deposit_id: u64, status: &str) -> (Key, U512, u64) {
+    let pending = _pending_deposit(deposit_id);
+    set_key("pending_deposits", &deposit_id.to_string(), None::<(Key, U512, u64)>);
+    let pending_deposit_count: u64 = get_key("cspr_holder_data", "pending_deposit_count");
+    set_key("cspr_holder_data", "pending_deposit_count", pending_deposit_count - 1);
+    let mut deposit: BTreeMap<String, String> = get_key("deposits", &deposit_id.to_string());
+    deposit.insert("status".to_string(), status.to_string());
+    set_key("deposits", &deposit_id.to_string(), deposit);
+    pending
+}
+
+fn _contract_purse() -> URef {
+    let contract_purse_key = runtime::get_key("contract_purse").unwrap_or_revert();
+    *contract_purse_key.as_uref().unwrap_or_revert()
+}
+
+/// The cap refills linearly, a full `daily_unlock_cap` per `UNLOCK_CAP_WINDOW`, so any 24h span stays bounded.
+fn _available_unlock_cap(daily_unlock_cap: U512) -> U512 {
+    let (last_update, available) = get_key::<(u64, U512)>("cspr_holder_data", "unlock_cap_bucket");
+    let elapsed = u64::from(runtime::get_blocktime()).saturating_sub(last_update);
+    if (elapsed >= UNLOCK_CAP_WINDOW) {
+        return daily_unlock_cap;
+    }
+    let refill = daily_unlock_cap * U512::from(elapsed) / U512::from(UNLOCK_CAP_WINDOW);
+    daily_unlock_cap.min(available + refill)
+}
+
+/// Appends an event to the `cspr_holder_events` dictionary under the next sequence number.
+fn emit(event: Event) {
+    let index: u64 = get_key("cspr_holder_data", "event_count");
+    set_key("cspr_holder_events", &index.to_string(), event.into_map());
+    set_key("cspr_holder_data", "event_count", index + 1);
+}
+
+fn key_to_str(key: &Key) -> String {
+    match key {
+        Key::Account(account) => account.to_string(),
+        Key::Hash(package) => hex::encode(package),
+        _ => runtime::revert(ApiError::UnexpectedKeyVariant),
+    }
+}
+
 fn get_dictionary_seed_uref(name: &str) -> URef {
     let dictionary_seed_uref = match runtime::get_key(name) {
         Some(key) => key.into_uref().unwrap_or_revert(),
@@ -133,6 +922,34 @@ fn get_key<T: FromBytes + CLTyped + Default>(dictionary_name: &str, key: &str) -
     storage::dictionary_get(dictionary_seed_uref, key).unwrap_or_default().unwrap_or_default()
 }
 
+fn set_key<T: ToBytes + CLTyped>(dictionary_name: &str, key: &str, value: T) {
+    let dictionary_seed_uref = get_dictionary_seed_uref(dictionary_name);
+    storage::dictionary_put(dictionary_seed_uref, key, value)
+}
+
+/// `Key` has no `Default`, so an unset governance reverts instead of reading as a zero key.
+fn get_governance(dictionary_name: &str) -> Key {
+    let dictionary_seed_uref = get_dictionary_seed_uref(dictionary_name);
+    storage::dictionary_get(dictionary_seed_uref, "governance").unwrap_or_revert().unwrap_or_revert()
+}
+
+fn ret<T: CLTyped + ToBytes>(value: T) {
+    runtime::ret(CLValue::from_t(value).unwrap_or_revert())
+}
+
+/// Source-chain transaction ids are hashed so the dictionary item key stays at 64 chars.
+fn tx_id_key(tx_id: &str) -> String {
+    hex::encode(blake2b(tx_id))
+}
+
+/// Identifies an unlock request by its full content so approvals for differing payouts never combine.
+fn unlock_request_key(tx_id: &str, target: &Key, amount: U512) -> String {
+    let mut bytes = tx_id.to_bytes().unwrap_or_revert();
+    bytes.append(&mut target.to_bytes().unwrap_or_revert());
+    bytes.append(&mut amount.to_bytes().unwrap_or_revert());
+    hex::encode(blake2b(bytes))
+}
+
 fn endpoint(name: &str, param: Vec<Parameter>, ret: CLType) -> EntryPoint {
     EntryPoint::new(
         String::from(name),
@@ -153,17 +970,31 @@ fn get_caller() -> Key {
             contract_package_hash: _,
             contract_hash: _,
         } => (*account_hash).into(),
+        // Signers, the router and contract governance are all known by their package.
         CallStackElement::StoredContract {
-            contract_package_hash: _,
-            contract_hash,
-        } => (*contract_hash).into(),
+            contract_package_hash,
+            contract_hash: _,
+        } => (*contract_package_hash).into(),
+    }
+}
+
+/// Locks and unlocks stay blocked while governance has halted the holder.
+fn _when_not_paused() {
+    if (get_key::<bool>("cspr_holder_data", "paused")) {
+        runtime::revert(Error::Paused);
+    }
+}
+
+fn _when_not_wrapped() {
+    if (_wcspr().is_some()) {
+        runtime::revert(Error::UnlockDisabledInWrappedMode);
     }
 }
 
 fn _authorization_check() {
     if (
         get_caller() != 
-        Key::Account(get_key::<AccountHash>("cspr_holder_data", "governance"))
+        get_governance("cspr_holder_data")
     ) {
         runtime::revert(Error::NoAccessRights);
     }
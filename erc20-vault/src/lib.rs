@@ -0,0 +1,490 @@
+#![allow(unused_imports)]
+#![allow(unused_parens)]
+#![allow(non_snake_case)]
+
+extern crate alloc;
+
+use alloc::{
+    collections::BTreeMap,
+    string::String,
+};
+
+use contract::{
+    contract_api::{
+        runtime::{self, blake2b},
+        storage::{self, create_contract_package_at_hash},
+    },
+    unwrap_or_revert::UnwrapOrRevert
+};
+use types::{ApiError, CLType, CLTyped, CLValue, ContractHash, ContractPackageHash, Key, Parameter, RuntimeArgs, U256, URef, bytesrepr::{FromBytes, ToBytes}, contracts::{EntryPoint, EntryPointAccess, EntryPointType, EntryPoints, NamedKeys}, runtime_args, system::CallStackElement};
+
+pub enum Error {
+    DepositAmountTooSmall = 0,
+    NoAccessRights = 1,
+    WithdrawalAlreadyProcessed = 2,
+    TokenNotAllowed = 3,
+    InsufficientVaultBalance = 4,
+    NoPendingGovernance = 5,
+    GovernanceDelayNotPassed = 6,
+}
+
+impl From<Error> for ApiError {
+    fn from(error: Error) -> ApiError {
+        ApiError::User(error as u16)
+    }
+}
+
+/// Delay before a proposed governance can be applied: 2 days, block time being in milliseconds.
+const GOVERNANCE_DELAY: u64 = 172_800_000;
+
+pub enum Event {
+    TokenAllowed { token: Key },
+    TokenDisallowed { token: Key },
+    Deposit { token: Key, depositor: Key, amount: U256, to_chain_id: U256, to_address: String },
+    Withdrawal { tx_id: String, token: Key, recipient: Key, amount: U256 },
+}
+
+impl Event {
+    /// Flattens the event into the string map stored in the `erc20_vault_events` dictionary.
+    fn into_map(self) -> BTreeMap<String, String> {
+        let mut event = BTreeMap::new();
+        match self {
+            Event::TokenAllowed { token } => {
+                event.insert("event_type".to_string(), "token_allowed".to_string());
+                event.insert("token".to_string(), token.to_formatted_string());
+            }
+            Event::TokenDisallowed { token } => {
+                event.insert("event_type".to_string(), "token_disallowed".to_string());
+                event.insert("token".to_string(), token.to_formatted_string());
+            }
+            Event::Deposit { token, depositor, amount, to_chain_id, to_address } => {
+                event.insert("event_type".to_string(), "deposit".to_string());
+                event.insert("token".to_string(), token.to_formatted_string());
+                event.insert("depositor".to_string(), depositor.to_formatted_string());
+                event.insert("amount".to_string(), amount.to_string());
+                event.insert("to_chain_id".to_string(), to_chain_id.to_string());
+                event.insert("to_address".to_string(), to_address);
+            }
+            Event::Withdrawal { tx_id, token, recipient, amount } => {
+                event.insert("event_type".to_string(), "withdrawal".to_string());
+                event.insert("tx_id".to_string(), tx_id);
+                event.insert("token".to_string(), token.to_formatted_string());
+                event.insert("recipient".to_string(), recipient.to_formatted_string());
+                event.insert("amount".to_string(), amount.to_string());
+            }
+        }
+        event
+    }
+}
+
+/// Pulls `amount` of an allowlisted token from the caller, who must have approved the vault first.
+#[no_mangle]
+pub extern "C" fn deposit() {
+    let token: Key = runtime::get_named_arg("token");
+    let amount: U256 = runtime::get_named_arg("amount");
+    let to_chain_id: U256 = runtime::get_named_arg("to_chain_id");
+    let to_address: String = runtime::get_named_arg("to_address");
+    if (!get_key::<bool>("allowed_tokens", &key_to_str(&token))) {
+        runtime::revert(Error::TokenNotAllowed);
+    }
+    if (amount.is_zero()) {
+        runtime::revert(Error::DepositAmountTooSmall);
+    }
+    let depositor = get_caller();
+    runtime::call_contract::<()>(
+        token_hash(&token),
+        "transfer_from",
+        runtime_args! {
+            "owner" => depositor,
+            "recipient" => vault_key(),
+            "amount" => amount
+        }
+    );
+
+    // Record the deposit so the MPC signers can mint on the destination chain.
+    let nonce: u64 = get_key("erc20_vault_data", "deposit_count");
+    let mut deposit = BTreeMap::new();
+    deposit.insert("depositor".to_string(), depositor.to_formatted_string());
+    deposit.insert("token".to_string(), token.to_formatted_string());
+    deposit.insert("amount".to_string(), amount.to_string());
+    deposit.insert("to_chain_id".to_string(), to_chain_id.to_string());
+    deposit.insert("to_address".to_string(), to_address.clone());
+    deposit.insert("timestamp".to_string(), u64::from(runtime::get_blocktime()).to_string());
+    set_key("deposits", &nonce.to_string(), deposit);
+    set_key("erc20_vault_data", "deposit_count", nonce + 1);
+    let deposited: U256 = get_key("deposited", &key_to_str(&token));
+    set_key("deposited", &key_to_str(&token), deposited + amount);
+    emit(Event::Deposit { token, depositor, amount, to_chain_id, to_address });
+}
+
+/// Releases tokens bridged back from another chain. Delisted tokens can still be withdrawn,
+/// the allowlist only stops new deposits.
+#[no_mangle]
+pub extern "C" fn withdraw() {
+    _authorization_check();
+    let tx_id: String = runtime::get_named_arg("tx_id");
+    let token: Key = runtime::get_named_arg("token");
+    let recipient: Key = runtime::get_named_arg("recipient");
+    let amount: U256 = runtime::get_named_arg("amount");
+    // Each source-chain transaction can only release tokens once.
+    if (get_key::<bool>("processed_withdrawals", &tx_id_key(&tx_id))) {
+        runtime::revert(Error::WithdrawalAlreadyProcessed);
+    }
+    set_key("processed_withdrawals", &tx_id_key(&tx_id), true);
+    if (amount > _locked(&token)) {
+        runtime::revert(Error::InsufficientVaultBalance);
+    }
+    let withdrawn: U256 = get_key("withdrawn", &key_to_str(&token));
+    set_key("withdrawn", &key_to_str(&token), withdrawn + amount);
+    runtime::call_contract::<()>(
+        token_hash(&token),
+        "transfer",
+        runtime_args! {
+            "recipient" => recipient,
+            "amount" => amount
+        }
+    );
+    emit(Event::Withdrawal { tx_id, token, recipient, amount });
+}
+
+#[no_mangle]
+pub extern "C" fn allow_token() {
+    _authorization_check();
+    let token: Key = runtime::get_named_arg("token");
+    set_key("allowed_tokens", &key_to_str(&token), true);
+    emit(Event::TokenAllowed { token });
+}
+
+#[no_mangle]
+pub extern "C" fn disallow_token() {
+    _authorization_check();
+    let token: Key = runtime::get_named_arg("token");
+    set_key("allowed_tokens", &key_to_str(&token), false);
+    emit(Event::TokenDisallowed { token });
+}
+
+#[no_mangle]
+pub extern "C" fn is_allowed_token() {
+    let token: Key = runtime::get_named_arg("token");
+    let val: bool = get_key("allowed_tokens", &key_to_str(&token));
+    ret(val)
+}
+
+#[no_mangle]
+pub extern "C" fn is_processed() {
+    let tx_id: String = runtime::get_named_arg("tx_id");
+    let val: bool = get_key("processed_withdrawals", &tx_id_key(&tx_id));
+    ret(val)
+}
+
+#[no_mangle]
+pub extern "C" fn locked() {
+    let token: Key = runtime::get_named_arg("token");
+    ret(_locked(&token))
+}
+
+#[no_mangle]
+pub extern "C" fn total_deposited() {
+    let token: Key = runtime::get_named_arg("token");
+    let val: U256 = get_key("deposited", &key_to_str(&token));
+    ret(val)
+}
+
+#[no_mangle]
+pub extern "C" fn total_withdrawn() {
+    let token: Key = runtime::get_named_arg("token");
+    let val: U256 = get_key("withdrawn", &key_to_str(&token));
+    ret(val)
+}
+
+#[no_mangle]
+pub extern "C" fn deposit_count() {
+    let val: u64 = get_key("erc20_vault_data", "deposit_count");
+    ret(val)
+}
+
+#[no_mangle]
+pub extern "C" fn governance() {
+    let val: Key = get_governance("erc20_vault_data");
+    ret(val)
+}
+
+#[no_mangle]
+pub extern "C" fn pending_governance() {
+    let val: Option<Key> = get_key("erc20_vault_data", "pending_governance");
+    ret(val)
+}
+
+#[no_mangle]
+pub extern "C" fn propose_governance() {
+    _authorization_check();
+    let new_governance: Key = runtime::get_named_arg("new_governance");
+    let effective_time = u64::from(runtime::get_blocktime()) + GOVERNANCE_DELAY;
+    set_key("erc20_vault_data", "pending_governance", Some(new_governance));
+    set_key("erc20_vault_data", "governance_effective_time", effective_time);
+}
+
+#[no_mangle]
+pub extern "C" fn apply_governance() {
+    let pending_governance = get_key::<Option<Key>>("erc20_vault_data", "pending_governance")
+        .unwrap_or_revert_with(Error::NoPendingGovernance);
+    // The new key has to prove it is usable before it takes over.
+    if (get_caller() != pending_governance) {
+        runtime::revert(Error::NoAccessRights);
+    }
+    if (u64::from(runtime::get_blocktime()) < get_key::<u64>("erc20_vault_data", "governance_effective_time")) {
+        runtime::revert(Error::GovernanceDelayNotPassed);
+    }
+    set_key("erc20_vault_data", "governance", pending_governance);
+    set_key("erc20_vault_data", "pending_governance", None::<Key>);
+}
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let mut entry_points = EntryPoints::new();
+    entry_points.add_entry_point(endpoint(
+        "deposit",
+        vec![
+            Parameter::new("token", CLType::Key),
+            Parameter::new("amount", CLType::U256),
+            Parameter::new("to_chain_id", CLType::U256),
+            Parameter::new("to_address", CLType::String),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "withdraw",
+        vec![
+            Parameter::new("tx_id", CLType::String),
+            Parameter::new("token", CLType::Key),
+            Parameter::new("recipient", CLType::Key),
+            Parameter::new("amount", CLType::U256),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "allow_token",
+        vec![
+            Parameter::new("token", CLType::Key),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "disallow_token",
+        vec![
+            Parameter::new("token", CLType::Key),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "is_allowed_token",
+        vec![
+            Parameter::new("token", CLType::Key),
+        ],
+        CLType::Bool,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "is_processed",
+        vec![
+            Parameter::new("tx_id", CLType::String),
+        ],
+        CLType::Bool,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "locked",
+        vec![
+            Parameter::new("token", CLType::Key),
+        ],
+        CLType::U256,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "total_deposited",
+        vec![
+            Parameter::new("token", CLType::Key),
+        ],
+        CLType::U256,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "total_withdrawn",
+        vec![
+            Parameter::new("token", CLType::Key),
+        ],
+        CLType::U256,
+    ));
+    entry_points.add_entry_point(endpoint("deposit_count", vec![], CLType::U64));
+    entry_points.add_entry_point(endpoint("governance", vec![], CLType::Key));
+    entry_points.add_entry_point(endpoint("pending_governance", vec![], Option::<Key>::cl_type()));
+    entry_points.add_entry_point(endpoint(
+        "propose_governance",
+        vec![
+            Parameter::new("new_governance", CLType::Key),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint("apply_governance", vec![], CLType::Unit));
+
+    let governance: Key = runtime::get_named_arg("governance");
+
+    let dictionary_seed_uref = storage::new_dictionary("erc20_vault_data").unwrap_or_revert();
+    storage::dictionary_put(
+        dictionary_seed_uref,
+        "governance",
+        governance
+    );
+    let mut named_keys = NamedKeys::new();
+    named_keys.insert(
+        "erc20_vault_data".to_string(),
+        dictionary_seed_uref.into()
+    );
+    named_keys.insert(
+        "allowed_tokens".to_string(),
+        storage::new_dictionary("allowed_tokens").unwrap_or_revert().into()
+    );
+    named_keys.insert(
+        "deposits".to_string(),
+        storage::new_dictionary("deposits").unwrap_or_revert().into()
+    );
+    named_keys.insert(
+        "deposited".to_string(),
+        storage::new_dictionary("deposited").unwrap_or_revert().into()
+    );
+    named_keys.insert(
+        "withdrawn".to_string(),
+        storage::new_dictionary("withdrawn").unwrap_or_revert().into()
+    );
+    named_keys.insert(
+        "processed_withdrawals".to_string(),
+        storage::new_dictionary("processed_withdrawals").unwrap_or_revert().into()
+    );
+    named_keys.insert(
+        "erc20_vault_events".to_string(),
+        storage::new_dictionary("erc20_vault_events").unwrap_or_revert().into()
+    );
+
+    let (contract_package_hash, access_uref) = create_contract_package_at_hash();
+    // Add new version to the package.
+    let (contract_hash, _) =
+        storage::add_contract_version(contract_package_hash, entry_points, named_keys);
+    // Save contract and contract hash in the caller's context.
+    runtime::put_key("ERC20_Vault", contract_hash.into());
+    runtime::put_key("ERC20_Vault_hash", storage::new_uref(contract_hash).into());
+    runtime::put_key("ERC20_Vault_package_hash", storage::new_uref(contract_package_hash).into());
+    // Save access_uref
+    runtime::put_key("access_uref", access_uref.into());
+    // Save contract_hash under the contract's dictionary to be accessed through the contract's endpoints.
+    storage::dictionary_put(
+        dictionary_seed_uref,
+        "contract_hash",
+        contract_hash,
+    );
+    storage::dictionary_put(
+        dictionary_seed_uref,
+        "contract_package_hash",
+        contract_package_hash,
+    );
+}
+
+/// What the vault still holds of `token`, the most any withdrawal can release.
+fn _locked(token: &Key) -> U256 {
+    let deposited: U256 = get_key("deposited", &key_to_str(token));
+    let withdrawn: U256 = get_key("withdrawn", &key_to_str(token));
+    deposited - withdrawn
+}
+
+/// The vault's own identity towards tokens, which see contract callers as their package `Key::Hash`.
+fn vault_key() -> Key {
+    Key::from(get_key::<ContractPackageHash>("erc20_vault_data", "contract_package_hash"))
+}
+
+fn token_hash(token: &Key) -> ContractHash {
+    ContractHash::new(token.into_hash().unwrap_or_revert())
+}
+
+/// Appends an event to the `erc20_vault_events` dictionary, indexed by the running `event_count`.
+fn emit(event: Event) {
+    let index: u64 = get_key("erc20_vault_data", "event_count");
+    set_key("erc20_vault_events", &index.to_string(), event.into_map());
+    set_key("erc20_vault_data", "event_count", index + 1);
+}
+
+fn key_to_str(key: &Key) -> String {
+    match key {
+        Key::Account(account) => account.to_string(),
+        Key::Hash(package) => hex::encode(package),
+        _ => runtime::revert(ApiError::UnexpectedKeyVariant),
+    }
+}
+
+fn get_dictionary_seed_uref(name: &str) -> URef {
+    let dictionary_seed_uref = match runtime::get_key(name) {
+        Some(key) => key.into_uref().unwrap_or_revert(),
+        None => {
+            let new_dict = storage::new_dictionary(name).unwrap_or_revert();
+            let key = storage::new_uref(new_dict).into();
+            runtime::put_key(name, key);
+            new_dict
+        },
+    };
+    dictionary_seed_uref
+}
+
+fn get_key<T: FromBytes + CLTyped + Default>(dictionary_name: &str, key: &str) -> T {
+    let dictionary_seed_uref = get_dictionary_seed_uref(dictionary_name);
+    storage::dictionary_get(dictionary_seed_uref, key).unwrap_or_default().unwrap_or_default()
+}
+
+fn set_key<T: ToBytes + CLTyped>(dictionary_name: &str, key: &str, value: T) {
+    let dictionary_seed_uref = get_dictionary_seed_uref(dictionary_name);
+    storage::dictionary_put(dictionary_seed_uref, key, value)
+}
+
+/// `Key` has no `Default`, so an unset governance reverts instead of reading as a zero key.
+fn get_governance(dictionary_name: &str) -> Key {
+    let dictionary_seed_uref = get_dictionary_seed_uref(dictionary_name);
+    storage::dictionary_get(dictionary_seed_uref, "governance").unwrap_or_revert().unwrap_or_revert()
+}
+
+fn ret<T: CLTyped + ToBytes>(value: T) {
+    runtime::ret(CLValue::from_t(value).unwrap_or_revert())
+}
+
+/// Source-chain transaction ids are hashed so the dictionary item key stays at 64 chars.
+fn tx_id_key(tx_id: &str) -> String {
+    hex::encode(blake2b(tx_id))
+}
+
+fn endpoint(name: &str, param: Vec<Parameter>, ret: CLType) -> EntryPoint {
+    EntryPoint::new(
+        String::from(name),
+        param,
+        ret,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    )
+}
+
+fn get_caller() -> Key {
+    let mut callstack = runtime::get_call_stack();
+    callstack.pop();
+    match callstack.last().unwrap_or_revert() {
+        CallStackElement::Session { account_hash } => (*account_hash).into(),
+        CallStackElement::StoredSession {
+            account_hash,
+            contract_package_hash: _,
+            contract_hash: _,
+        } => (*account_hash).into(),
+        CallStackElement::StoredContract {
+            contract_package_hash,
+            contract_hash: _,
+        } => (*contract_package_hash).into(),
+    }
+}
+
+fn _authorization_check() {
+    if (
+        get_caller() != 
+        get_governance("erc20_vault_data")
+    ) {
+        runtime::revert(Error::NoAccessRights);
+    }
+}
@@ -19,6 +19,21 @@ pub enum Error {
     CannotBurnFromZeroHash = 1,
     BurnAmountExceedsBalance = 2,
     NoAccessRights = 3,
+    SwapInAlreadyProcessed = 4,
+    NoPendingGovernance = 5,
+    GovernanceDelayNotPassed = 6,
+    InvalidRole = 7,
+    InsufficientBalance = 8,
+    InsufficientAllowance = 9,
+    TransferToZeroKey = 10,
+    ForcedBurnDisabled = 11,
+    Paused = 12,
+    AccountFrozen = 13,
+    AccountNotFrozen = 14,
+    CapExceeded = 15,
+    MintQuotaExceeded = 16,
+    MintRateLimitExceeded = 17,
+    AllowanceOverflow = 18,
 }
 
 impl From<Error> for ApiError {
@@ -27,6 +42,110 @@ impl From<Error> for ApiError {
     }
 }
 
+/// Delay before a proposed governance can be applied: 2 days, block time being in milliseconds.
+const GOVERNANCE_DELAY: u64 = 172_800_000;
+
+/// Window over which a minter's rate limit refills completely: 24 hours, in milliseconds.
+const MINT_RATE_WINDOW: u64 = 86_400_000;
+
+const ADMIN_ROLE: &str = "admin";
+const MINTER_ROLE: &str = "minter";
+const BURNER_ROLE: &str = "burner";
+const PAUSER_ROLE: &str = "pauser";
+const ROLES: [&str; 4] = [ADMIN_ROLE, MINTER_ROLE, BURNER_ROLE, PAUSER_ROLE];
+
+pub enum Event {
+    Transfer { from: Key, to: Key, amount: U256 },
+    Approval { owner: Key, spender: Key, allowance: U256 },
+    Mint { owner: Key, amount: U256 },
+    Burn { owner: Key, amount: U256 },
+    SwapIn { tx_hash: String, account: Key, amount: U256 },
+    SwapOut { account: Key, amount: U256, bind_address: String, to_chain_id: U256 },
+    RoleGranted { role: String, account: Key },
+    RoleRevoked { role: String, account: Key },
+    Paused { account: Key },
+    Unpaused { account: Key },
+    Frozen { account: Key },
+    Unfrozen { account: Key },
+    Wiped { account: Key, amount: U256 },
+}
+
+impl Event {
+    /// Flattens the event into the string map stored in the `events` dictionary.
+    fn into_map(self) -> BTreeMap<String, String> {
+        let mut event = BTreeMap::new();
+        match self {
+            Event::Transfer { from, to, amount } => {
+                event.insert("event_type".to_string(), "transfer".to_string());
+                event.insert("from".to_string(), from.to_formatted_string());
+                event.insert("to".to_string(), to.to_formatted_string());
+                event.insert("amount".to_string(), amount.to_string());
+            }
+            Event::Approval { owner, spender, allowance } => {
+                event.insert("event_type".to_string(), "approval".to_string());
+                event.insert("owner".to_string(), owner.to_formatted_string());
+                event.insert("spender".to_string(), spender.to_formatted_string());
+                event.insert("allowance".to_string(), allowance.to_string());
+            }
+            Event::Mint { owner, amount } => {
+                event.insert("event_type".to_string(), "mint".to_string());
+                event.insert("owner".to_string(), owner.to_formatted_string());
+                event.insert("amount".to_string(), amount.to_string());
+            }
+            Event::Burn { owner, amount } => {
+                event.insert("event_type".to_string(), "burn".to_string());
+                event.insert("owner".to_string(), owner.to_formatted_string());
+                event.insert("amount".to_string(), amount.to_string());
+            }
+            Event::SwapIn { tx_hash, account, amount } => {
+                event.insert("event_type".to_string(), "swap_in".to_string());
+                event.insert("tx_hash".to_string(), tx_hash);
+                event.insert("account".to_string(), account.to_formatted_string());
+                event.insert("amount".to_string(), amount.to_string());
+            }
+            Event::SwapOut { account, amount, bind_address, to_chain_id } => {
+                event.insert("event_type".to_string(), "swap_out".to_string());
+                event.insert("account".to_string(), account.to_formatted_string());
+                event.insert("amount".to_string(), amount.to_string());
+                event.insert("bind_address".to_string(), bind_address);
+                event.insert("to_chain_id".to_string(), to_chain_id.to_string());
+            }
+            Event::RoleGranted { role, account } => {
+                event.insert("event_type".to_string(), "role_granted".to_string());
+                event.insert("role".to_string(), role);
+                event.insert("account".to_string(), account.to_formatted_string());
+            }
+            Event::RoleRevoked { role, account } => {
+                event.insert("event_type".to_string(), "role_revoked".to_string());
+                event.insert("role".to_string(), role);
+                event.insert("account".to_string(), account.to_formatted_string());
+            }
+            Event::Paused { account } => {
+                event.insert("event_type".to_string(), "paused".to_string());
+                event.insert("account".to_string(), account.to_formatted_string());
+            }
+            Event::Unpaused { account } => {
+                event.insert("event_type".to_string(), "unpaused".to_string());
+                event.insert("account".to_string(), account.to_formatted_string());
+            }
+            Event::Frozen { account } => {
+                event.insert("event_type".to_string(), "frozen".to_string());
+                event.insert("account".to_string(), account.to_formatted_string());
+            }
+            Event::Unfrozen { account } => {
+                event.insert("event_type".to_string(), "unfrozen".to_string());
+                event.insert("account".to_string(), account.to_formatted_string());
+            }
+            Event::Wiped { account, amount } => {
+                event.insert("event_type".to_string(), "wiped".to_string());
+                event.insert("account".to_string(), account.to_formatted_string());
+                event.insert("amount".to_string(), amount.to_string());
+            }
+        }
+        event
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn name() {
     let val: String = get_key("token_metadata", "name");
@@ -51,6 +170,24 @@ pub extern "C" fn total_supply() {
     ret(val)
 }
 
+#[no_mangle]
+pub extern "C" fn cap() {
+    let val: Option<U256> = get_key("token_metadata", "max_supply");
+    ret(val)
+}
+
+#[no_mangle]
+pub extern "C" fn set_cap() {
+    _authorization_check();
+    let max_supply: Option<U256> = runtime::get_named_arg("max_supply");
+    if let Some(max_supply) = max_supply {
+        if (max_supply < get_key::<U256>("token_metadata", "total_supply")) {
+            runtime::revert(Error::CapExceeded);
+        }
+    }
+    set_key("token_metadata", "max_supply", max_supply);
+}
+
 #[no_mangle]
 pub extern "C" fn balance_of() {
     let account: Key = runtime::get_named_arg("account");
@@ -62,7 +199,7 @@ pub extern "C" fn balance_of() {
 pub extern "C" fn allowance() {
     let owner: Key = runtime::get_named_arg("owner");
     let spender: Key = runtime::get_named_arg("spender");
-    let val: U256 = get_key_runtime(&allowance_key(&owner, &spender));
+    let val: U256 = _allowance(&owner, &spender);
     ret(val)
 }
 
@@ -77,8 +214,32 @@ pub extern "C" fn approve() {
     );
 }
 
+#[no_mangle]
+pub extern "C" fn increase_allowance() {
+    let spender: Key = runtime::get_named_arg("spender");
+    let delta: U256 = runtime::get_named_arg("delta");
+    let owner = get_caller();
+    let allowance = _allowance(&owner, &spender)
+        .checked_add(delta)
+        .unwrap_or_revert_with(Error::AllowanceOverflow);
+    _approve(owner, spender, allowance);
+}
+
+#[no_mangle]
+pub extern "C" fn decrease_allowance() {
+    let spender: Key = runtime::get_named_arg("spender");
+    let delta: U256 = runtime::get_named_arg("delta");
+    let owner = get_caller();
+    let allowance = _allowance(&owner, &spender);
+    if (allowance < delta) {
+        runtime::revert(Error::InsufficientAllowance);
+    }
+    _approve(owner, spender, allowance.sub(delta));
+}
+
 #[no_mangle]
 pub extern "C" fn transfer() {
+    _when_not_paused();
     let recipient: Key = runtime::get_named_arg("recipient");
     let amount: U256 = runtime::get_named_arg("amount");
     _transfer(
@@ -90,6 +251,7 @@ pub extern "C" fn transfer() {
 
 #[no_mangle]
 pub extern "C" fn transfer_from() {
+    _when_not_paused();
     let owner: Key = runtime::get_named_arg("owner");
     let recipient: Key = runtime::get_named_arg("recipient");
     let amount: U256 = runtime::get_named_arg("amount");
@@ -98,33 +260,246 @@ pub extern "C" fn transfer_from() {
 
 #[no_mangle]
 pub extern "C" fn mint() {
-    _authorization_check();
+    _role_check(MINTER_ROLE);
+    _when_not_paused();
     let owner: Key = runtime::get_named_arg("owner");
     let amount: U256 = runtime::get_named_arg("amount");
-    if (owner == Key::Hash([0u8; 32]) || owner == Key::Account(AccountHash::new([0u8; 32]))) {
-        runtime::revert(Error::CannotMintToZeroHash);
+    _consume_mint_limits(&get_caller(), amount);
+    _mint(owner, amount);
+}
+
+#[no_mangle]
+pub extern "C" fn set_minter_limits() {
+    _role_check(ADMIN_ROLE);
+    let minter: Key = runtime::get_named_arg("minter");
+    let quota: Option<U256> = runtime::get_named_arg("quota");
+    let rate_limit: Option<U256> = runtime::get_named_arg("rate_limit");
+    let minter_key = key_to_str(&minter);
+    set_key("mint_quotas", &minter_key, quota);
+    set_key("mint_rate_limits", &minter_key, rate_limit);
+    // The rate limit starts out with its whole window available.
+    if let Some(rate_limit) = rate_limit {
+        set_key("mint_rate_buckets", &minter_key, (u64::from(runtime::get_blocktime()), rate_limit));
     }
-    let total_supply = get_key::<U256>("token_metadata", "total_supply");
-    set_key("token_metadata", "total_supply", total_supply.add(amount));
-    let balance = get_key::<U256>("balances", &key_to_str(&owner));
-    set_key("balances", &key_to_str(&owner), balance.add(amount));
+}
+
+/// What the minter can still mint right now, `None` when it has neither a quota nor a rate limit.
+#[no_mangle]
+pub extern "C" fn remaining_mint_limit() {
+    let minter: Key = runtime::get_named_arg("minter");
+    let minter_key = key_to_str(&minter);
+    let quota = get_key::<Option<U256>>("mint_quotas", &minter_key);
+    let available_rate = get_key::<Option<U256>>("mint_rate_limits", &minter_key)
+        .map(|rate_limit| _available_mint_rate(&minter_key, rate_limit));
+    let val: Option<U256> = match (quota, available_rate) {
+        (Some(quota), Some(available_rate)) => Some(quota.min(available_rate)),
+        (quota, available_rate) => quota.or(available_rate),
+    };
+    ret(val)
 }
 
 #[no_mangle]
 pub extern "C" fn burn() {
-    _authorization_check();
+    _role_check(BURNER_ROLE);
+    if (get_key::<bool>("external", "forced_burn_disabled")) {
+        runtime::revert(Error::ForcedBurnDisabled);
+    }
     let owner: Key = runtime::get_named_arg("owner");
     let amount: U256 = runtime::get_named_arg("amount");
-    if (owner == Key::Hash([0u8; 32]) || owner == Key::Account(AccountHash::new([0u8; 32]))) {
-        runtime::revert(Error::CannotBurnFromZeroHash);
+    _when_not_frozen(&owner);
+    _burn(owner, amount);
+}
+
+#[no_mangle]
+pub extern "C" fn burn_self() {
+    let amount: U256 = runtime::get_named_arg("amount");
+    let owner = get_caller();
+    _when_not_frozen(&owner);
+    _burn(owner, amount);
+}
+
+#[no_mangle]
+pub extern "C" fn burn_from() {
+    let owner: Key = runtime::get_named_arg("owner");
+    let amount: U256 = runtime::get_named_arg("amount");
+    let spender = get_caller();
+    _when_not_frozen(&owner);
+    _when_not_frozen(&spender);
+    let allowance = _allowance(&owner, &spender);
+    if (allowance < amount) {
+        runtime::revert(Error::InsufficientAllowance);
     }
-    let balance = get_key::<U256>("balances", &key_to_str(&owner));
-    if (balance < amount) {
-        runtime::revert(Error::BurnAmountExceedsBalance);
+    _burn(owner, amount);
+    _approve(owner, spender, allowance.sub(amount));
+}
+
+/// Permanently stops burner roles from burning other accounts' tokens through `burn`.
+#[no_mangle]
+pub extern "C" fn disable_forced_burn() {
+    _role_check(ADMIN_ROLE);
+    set_key("external", "forced_burn_disabled", true);
+}
+
+#[no_mangle]
+pub extern "C" fn swap_in() {
+    _role_check(MINTER_ROLE);
+    _when_not_paused();
+    let tx_hash: String = runtime::get_named_arg("tx_hash");
+    let account: Key = runtime::get_named_arg("account");
+    let amount: U256 = runtime::get_named_arg("amount");
+    // A source-chain transaction can only ever be minted once.
+    let swap_key = tx_hash_key(&tx_hash);
+    if (get_key::<bool>("swap_ins", &swap_key)) {
+        runtime::revert(Error::SwapInAlreadyProcessed);
     }
-    set_key("balances", &key_to_str(&owner), balance.sub(amount));
-    let total_supply = get_key::<U256>("token_metadata", "total_supply");
-    set_key("token_metadata", "total_supply", total_supply.sub(amount));
+    set_key("swap_ins", &swap_key, true);
+    _consume_mint_limits(&get_caller(), amount);
+    _mint(account, amount);
+    emit(Event::SwapIn { tx_hash, account, amount });
+}
+
+#[no_mangle]
+pub extern "C" fn swap_out() {
+    _when_not_paused();
+    let amount: U256 = runtime::get_named_arg("amount");
+    let bind_address: String = runtime::get_named_arg("bind_address");
+    let to_chain_id: U256 = runtime::get_named_arg("to_chain_id");
+    let account = get_caller();
+    _when_not_frozen(&account);
+    _burn(account, amount);
+    emit(Event::SwapOut { account, amount, bind_address, to_chain_id });
+}
+
+#[no_mangle]
+pub extern "C" fn is_frozen() {
+    let account: Key = runtime::get_named_arg("account");
+    let val: bool = get_key("frozen", &key_to_str(&account));
+    ret(val)
+}
+
+#[no_mangle]
+pub extern "C" fn freeze() {
+    _authorization_check();
+    let account: Key = runtime::get_named_arg("account");
+    set_key("frozen", &key_to_str(&account), true);
+    emit(Event::Frozen { account });
+}
+
+#[no_mangle]
+pub extern "C" fn unfreeze() {
+    _authorization_check();
+    let account: Key = runtime::get_named_arg("account");
+    set_key("frozen", &key_to_str(&account), false);
+    emit(Event::Unfrozen { account });
+}
+
+/// Burns the whole balance of a frozen account, e.g. funds stolen from the bridge.
+#[no_mangle]
+pub extern "C" fn wipe_frozen() {
+    _authorization_check();
+    let account: Key = runtime::get_named_arg("account");
+    if (!get_key::<bool>("frozen", &key_to_str(&account))) {
+        runtime::revert(Error::AccountNotFrozen);
+    }
+    if (get_key::<bool>("external", "forced_burn_disabled")) {
+        runtime::revert(Error::ForcedBurnDisabled);
+    }
+    let amount = get_key::<U256>("balances", &key_to_str(&account));
+    _burn(account, amount);
+    emit(Event::Wiped { account, amount });
+}
+
+#[no_mangle]
+pub extern "C" fn paused() {
+    let val: bool = get_key("external", "paused");
+    ret(val)
+}
+
+#[no_mangle]
+pub extern "C" fn pause() {
+    _role_check(PAUSER_ROLE);
+    set_key("external", "paused", true);
+    emit(Event::Paused { account: get_caller() });
+}
+
+#[no_mangle]
+pub extern "C" fn unpause() {
+    _role_check(PAUSER_ROLE);
+    set_key("external", "paused", false);
+    emit(Event::Unpaused { account: get_caller() });
+}
+
+#[no_mangle]
+pub extern "C" fn has_role() {
+    let role: String = runtime::get_named_arg("role");
+    let account: Key = runtime::get_named_arg("account");
+    ret(_has_role(&role, &account))
+}
+
+#[no_mangle]
+pub extern "C" fn grant_role() {
+    _role_check(ADMIN_ROLE);
+    let role: String = runtime::get_named_arg("role");
+    let account: Key = runtime::get_named_arg("account");
+    _grant_role(&role, account);
+}
+
+#[no_mangle]
+pub extern "C" fn revoke_role() {
+    _role_check(ADMIN_ROLE);
+    let role: String = runtime::get_named_arg("role");
+    let account: Key = runtime::get_named_arg("account");
+    _revoke_role(&role, account);
+}
+
+#[no_mangle]
+pub extern "C" fn renounce_role() {
+    let role: String = runtime::get_named_arg("role");
+    _revoke_role(&role, get_caller());
+}
+
+#[no_mangle]
+pub extern "C" fn governance() {
+    let val: Key = get_governance("external");
+    ret(val)
+}
+
+#[no_mangle]
+pub extern "C" fn pending_governance() {
+    let val: Option<Key> = get_key("external", "pending_governance");
+    ret(val)
+}
+
+#[no_mangle]
+pub extern "C" fn propose_governance() {
+    _authorization_check();
+    let new_governance: Key = runtime::get_named_arg("new_governance");
+    let effective_time = u64::from(runtime::get_blocktime()) + GOVERNANCE_DELAY;
+    set_key("external", "pending_governance", Some(new_governance));
+    set_key("external", "governance_effective_time", effective_time);
+}
+
+#[no_mangle]
+pub extern "C" fn apply_governance() {
+    let pending_governance = get_key::<Option<Key>>("external", "pending_governance")
+        .unwrap_or_revert_with(Error::NoPendingGovernance);
+    // The new key has to prove it is usable before it takes over.
+    if (get_caller() != pending_governance) {
+        runtime::revert(Error::NoAccessRights);
+    }
+    if (u64::from(runtime::get_blocktime()) < get_key::<u64>("external", "governance_effective_time")) {
+        runtime::revert(Error::GovernanceDelayNotPassed);
+    }
+    // Roles follow governance, so the previous key loses its privileges.
+    let previous_governance = get_governance("external");
+    for role in ROLES.iter() {
+        if (_has_role(role, &previous_governance)) {
+            _revoke_role(role, previous_governance);
+            _grant_role(role, pending_governance);
+        }
+    }
+    set_key("external", "governance", pending_governance);
+    set_key("external", "pending_governance", None::<Key>);
 }
 
 #[no_mangle]
@@ -133,13 +508,19 @@ pub extern "C" fn call() {
     let token_symbol: String = runtime::get_named_arg("token_symbol");
     let token_decimals: u8 = runtime::get_named_arg("token_decimals");
     let token_total_supply: U256 = runtime::get_named_arg("token_total_supply");
-    let governance: AccountHash = runtime::get_named_arg("governance");
+    let token_max_supply: Option<U256> = runtime::get_named_arg("token_max_supply");
+    let governance: Key = runtime::get_named_arg("governance");
+    if let Some(max_supply) = token_max_supply {
+        if (token_total_supply > max_supply) {
+            runtime::revert(Error::CapExceeded);
+        }
+    }
 
     let mut entry_points = EntryPoints::new();
     entry_points.add_entry_point(endpoint("name", vec![], CLType::String));
     entry_points.add_entry_point(endpoint("symbol", vec![], CLType::String));
     entry_points.add_entry_point(endpoint("decimals", vec![], CLType::U8));
-    entry_points.add_entry_point(endpoint("total_supply", vec![], CLType::U32));
+    entry_points.add_entry_point(endpoint("total_supply", vec![], CLType::U256));
     entry_points.add_entry_point(endpoint(
         "transfer",
         vec![
@@ -148,6 +529,14 @@ pub extern "C" fn call() {
         ],
         CLType::Unit,
     ));
+    entry_points.add_entry_point(endpoint("cap", vec![], Option::<U256>::cl_type()));
+    entry_points.add_entry_point(endpoint(
+        "set_cap",
+        vec![
+            Parameter::new("max_supply", Option::<U256>::cl_type()),
+        ],
+        CLType::Unit,
+    ));
     entry_points.add_entry_point(endpoint(
         "balance_of",
         vec![Parameter::new("account", CLType::Key)],
@@ -169,6 +558,22 @@ pub extern "C" fn call() {
         ],
         CLType::Unit,
     ));
+    entry_points.add_entry_point(endpoint(
+        "increase_allowance",
+        vec![
+            Parameter::new("spender", CLType::Key),
+            Parameter::new("delta", CLType::U256),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "decrease_allowance",
+        vec![
+            Parameter::new("spender", CLType::Key),
+            Parameter::new("delta", CLType::U256),
+        ],
+        CLType::Unit,
+    ));
     entry_points.add_entry_point(endpoint(
         "transfer_from",
         vec![
@@ -186,6 +591,22 @@ pub extern "C" fn call() {
         ],
         CLType::Unit,
     ));
+    entry_points.add_entry_point(endpoint(
+        "set_minter_limits",
+        vec![
+            Parameter::new("minter", CLType::Key),
+            Parameter::new("quota", Option::<U256>::cl_type()),
+            Parameter::new("rate_limit", Option::<U256>::cl_type()),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "remaining_mint_limit",
+        vec![
+            Parameter::new("minter", CLType::Key),
+        ],
+        Option::<U256>::cl_type(),
+    ));
     entry_points.add_entry_point(endpoint(
         "burn",
         vec![
@@ -194,6 +615,112 @@ pub extern "C" fn call() {
         ],
         CLType::Unit,
     ));
+    entry_points.add_entry_point(endpoint(
+        "burn_self",
+        vec![
+            Parameter::new("amount", CLType::U256),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "burn_from",
+        vec![
+            Parameter::new("owner", CLType::Key),
+            Parameter::new("amount", CLType::U256),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint("disable_forced_burn", vec![], CLType::Unit));
+    entry_points.add_entry_point(endpoint(
+        "swap_in",
+        vec![
+            Parameter::new("tx_hash", CLType::String),
+            Parameter::new("account", CLType::Key),
+            Parameter::new("amount", CLType::U256),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "swap_out",
+        vec![
+            Parameter::new("amount", CLType::U256),
+            Parameter::new("bind_address", CLType::String),
+            Parameter::new("to_chain_id", CLType::U256),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "is_frozen",
+        vec![
+            Parameter::new("account", CLType::Key),
+        ],
+        CLType::Bool,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "freeze",
+        vec![
+            Parameter::new("account", CLType::Key),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "unfreeze",
+        vec![
+            Parameter::new("account", CLType::Key),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "wipe_frozen",
+        vec![
+            Parameter::new("account", CLType::Key),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint("paused", vec![], CLType::Bool));
+    entry_points.add_entry_point(endpoint("pause", vec![], CLType::Unit));
+    entry_points.add_entry_point(endpoint("unpause", vec![], CLType::Unit));
+    entry_points.add_entry_point(endpoint(
+        "has_role",
+        vec![
+            Parameter::new("role", CLType::String),
+            Parameter::new("account", CLType::Key),
+        ],
+        CLType::Bool,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "grant_role",
+        vec![
+            Parameter::new("role", CLType::String),
+            Parameter::new("account", CLType::Key),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "revoke_role",
+        vec![
+            Parameter::new("role", CLType::String),
+            Parameter::new("account", CLType::Key),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "renounce_role",
+        vec![
+            Parameter::new("role", CLType::String),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint("governance", vec![], CLType::Key));
+    entry_points.add_entry_point(endpoint("pending_governance", vec![], Option::<Key>::cl_type()));
+    entry_points.add_entry_point(endpoint(
+        "propose_governance",
+        vec![
+            Parameter::new("new_governance", CLType::Key),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint("apply_governance", vec![], CLType::Unit));
 
     let dictionary_seed_uref = storage::new_dictionary("token_metadata").unwrap_or_revert();
     storage::dictionary_put(
@@ -216,18 +743,42 @@ pub extern "C" fn call() {
         "total_supply",
         token_total_supply
     );
+    storage::dictionary_put(
+        dictionary_seed_uref,
+        "max_supply",
+        token_max_supply
+    );
     let external_seed_uref = storage::new_dictionary("external").unwrap_or_revert();
     storage::dictionary_put(
         external_seed_uref,
         "governance",
         governance
     );
+    // Governance starts out holding every role.
+    let roles_seed_uref = storage::new_dictionary("roles").unwrap_or_revert();
+    for role in ROLES.iter() {
+        storage::dictionary_put(
+            roles_seed_uref,
+            &role_key(role, &governance),
+            true
+        );
+    }
     let balances_seed_uref = storage::new_dictionary("balances").unwrap_or_revert();
     storage::dictionary_put(
         balances_seed_uref,
         &key_to_str(&Key::Account(runtime::get_caller())),
         token_total_supply
     );
+    // The initial supply is recorded as the first event of the log.
+    let events_seed_uref = storage::new_dictionary("events").unwrap_or_revert();
+    storage::dictionary_put(
+        events_seed_uref,
+        "0",
+        Event::Mint {
+            owner: Key::Account(runtime::get_caller()),
+            amount: token_total_supply,
+        }.into_map()
+    );
     let mut named_keys = NamedKeys::new();
     named_keys.insert(
         "token_metadata".to_string(), 
@@ -241,6 +792,42 @@ pub extern "C" fn call() {
         "balances".to_string(), 
         balances_seed_uref.into()
     );
+    named_keys.insert(
+        "roles".to_string(),
+        roles_seed_uref.into()
+    );
+    named_keys.insert(
+        "allowances".to_string(),
+        storage::new_dictionary("allowances").unwrap_or_revert().into()
+    );
+    named_keys.insert(
+        "frozen".to_string(),
+        storage::new_dictionary("frozen").unwrap_or_revert().into()
+    );
+    named_keys.insert(
+        "mint_quotas".to_string(),
+        storage::new_dictionary("mint_quotas").unwrap_or_revert().into()
+    );
+    named_keys.insert(
+        "mint_rate_limits".to_string(),
+        storage::new_dictionary("mint_rate_limits").unwrap_or_revert().into()
+    );
+    named_keys.insert(
+        "mint_rate_buckets".to_string(),
+        storage::new_dictionary("mint_rate_buckets").unwrap_or_revert().into()
+    );
+    named_keys.insert(
+        "events".to_string(), 
+        events_seed_uref.into()
+    );
+    named_keys.insert(
+        "event_count".to_string(),
+        storage::new_uref(1u64).into()
+    );
+    named_keys.insert(
+        "swap_ins".to_string(),
+        storage::new_dictionary("swap_ins").unwrap_or_revert().into()
+    );
 
     let (contract_hash, _) =
         storage::new_locked_contract(entry_points, Some(named_keys), None, None);
@@ -249,24 +836,162 @@ pub extern "C" fn call() {
 }
 
 fn _transfer(sender: Key, recipient: Key, amount: U256) {
-    let new_sender_balance: U256 = (get_key::<U256>("balances", &key_to_str(&sender)) - amount);
-    set_key("balances", &key_to_str(&sender), new_sender_balance);
+    if (recipient == Key::Hash([0u8; 32]) || recipient == Key::Account(AccountHash::new([0u8; 32]))) {
+        runtime::revert(Error::TransferToZeroKey);
+    }
+    _when_not_frozen(&sender);
+    _when_not_frozen(&recipient);
+    let sender_balance = get_key::<U256>("balances", &key_to_str(&sender));
+    if (sender_balance < amount) {
+        runtime::revert(Error::InsufficientBalance);
+    }
+    set_key("balances", &key_to_str(&sender), sender_balance.sub(amount));
     let new_recipient_balance: U256 = (get_key::<U256>("balances", &key_to_str(&recipient)) + amount);
     set_key("balances", &key_to_str(&recipient), new_recipient_balance);
+    emit(Event::Transfer { from: sender, to: recipient, amount });
+}
+
+/// Draws a mint from the minter's quota and rate limit, those left unset don't restrict it.
+fn _consume_mint_limits(minter: &Key, amount: U256) {
+    let minter_key = key_to_str(minter);
+    if let Some(quota) = get_key::<Option<U256>>("mint_quotas", &minter_key) {
+        if (amount > quota) {
+            runtime::revert(Error::MintQuotaExceeded);
+        }
+        set_key("mint_quotas", &minter_key, Some(quota.sub(amount)));
+    }
+    if let Some(rate_limit) = get_key::<Option<U256>>("mint_rate_limits", &minter_key) {
+        let available = _available_mint_rate(&minter_key, rate_limit);
+        if (amount > available) {
+            runtime::revert(Error::MintRateLimitExceeded);
+        }
+        set_key("mint_rate_buckets", &minter_key, (u64::from(runtime::get_blocktime()), available.sub(amount)));
+    }
+}
+
+/// The rate limit refills linearly, a full `rate_limit` per `MINT_RATE_WINDOW`, so any 24h span stays bounded.
+fn _available_mint_rate(minter_key: &str, rate_limit: U256) -> U256 {
+    let (last_update, available) = get_key::<(u64, U256)>("mint_rate_buckets", minter_key);
+    let elapsed = u64::from(runtime::get_blocktime()).saturating_sub(last_update);
+    if (elapsed >= MINT_RATE_WINDOW) {
+        return rate_limit;
+    }
+    // A limit too large to scale, e.g. `U256::MAX` for "unlimited", refills in full.
+    let refill = rate_limit
+        .checked_mul(U256::from(elapsed))
+        .map_or(rate_limit, |scaled| scaled / U256::from(MINT_RATE_WINDOW));
+    rate_limit.min(available.saturating_add(refill))
+}
+
+fn _mint(owner: Key, amount: U256) {
+    if (owner == Key::Hash([0u8; 32]) || owner == Key::Account(AccountHash::new([0u8; 32]))) {
+        runtime::revert(Error::CannotMintToZeroHash);
+    }
+    _when_not_frozen(&owner);
+    let total_supply = get_key::<U256>("token_metadata", "total_supply");
+    if let Some(max_supply) = get_key::<Option<U256>>("token_metadata", "max_supply") {
+        if (total_supply.add(amount) > max_supply) {
+            runtime::revert(Error::CapExceeded);
+        }
+    }
+    set_key("token_metadata", "total_supply", total_supply.add(amount));
+    let balance = get_key::<U256>("balances", &key_to_str(&owner));
+    set_key("balances", &key_to_str(&owner), balance.add(amount));
+    emit(Event::Mint { owner, amount });
+}
+
+fn _burn(owner: Key, amount: U256) {
+    if (owner == Key::Hash([0u8; 32]) || owner == Key::Account(AccountHash::new([0u8; 32]))) {
+        runtime::revert(Error::CannotBurnFromZeroHash);
+    }
+    let balance = get_key::<U256>("balances", &key_to_str(&owner));
+    if (balance < amount) {
+        runtime::revert(Error::BurnAmountExceedsBalance);
+    }
+    set_key("balances", &key_to_str(&owner), balance.sub(amount));
+    let total_supply = get_key::<U256>("token_metadata", "total_supply");
+    set_key("token_metadata", "total_supply", total_supply.sub(amount));
+    emit(Event::Burn { owner, amount });
 }
 
 fn _transfer_from(owner: Key, recipient: Key, amount: U256) {
-    let key = allowance_key(&owner, &get_caller());
+    let spender = get_caller();
+    // A frozen spender cannot move funds through allowances it was granted earlier.
+    _when_not_frozen(&spender);
+    // The allowance is checked before any balance moves.
+    let allowance = _allowance(&owner, &spender);
+    if (allowance < amount) {
+        runtime::revert(Error::InsufficientAllowance);
+    }
     _transfer(owner, recipient, amount);
     _approve(
         owner,
-        get_caller(),
-        (get_key_runtime::<U256>(&key) - amount),
+        spender,
+        allowance.sub(amount),
     );
 }
 
+/// Pairs approved before the `allowances` dictionary existed are still read from their named key.
+fn _allowance(owner: &Key, spender: &Key) -> U256 {
+    let dictionary_seed_uref = get_dictionary_seed_uref("allowances");
+    match storage::dictionary_get::<U256>(dictionary_seed_uref, &allowance_key(owner, spender)).unwrap_or_revert() {
+        Some(allowance) => allowance,
+        None => get_key_runtime(&legacy_allowance_key(owner, spender)),
+    }
+}
+
+fn _set_allowance(owner: &Key, spender: &Key, amount: U256) {
+    set_key("allowances", &allowance_key(owner, spender), amount);
+    // Once the pair lives in the dictionary its legacy named key is dead weight.
+    let legacy_key = legacy_allowance_key(owner, spender);
+    if (runtime::has_key(&legacy_key)) {
+        runtime::remove_key(&legacy_key);
+    }
+}
+
 fn _approve(owner: Key, spender: Key, amount: U256) {
-    set_key_runtime(&allowance_key(&owner, &spender), amount);
+    _set_allowance(&owner, &spender, amount);
+    emit(Event::Approval { owner, spender, allowance: amount });
+}
+
+/// Appends an event to the `events` dictionary under the next sequence number.
+fn emit(event: Event) {
+    let index: u64 = get_key_runtime("event_count");
+    set_key("events", &index.to_string(), event.into_map());
+    set_key_runtime("event_count", index + 1);
+}
+
+/// Transfers, mints and swaps stay blocked while a pauser has halted the token.
+fn _when_not_paused() {
+    if (get_key::<bool>("external", "paused")) {
+        runtime::revert(Error::Paused);
+    }
+}
+
+fn _when_not_frozen(account: &Key) {
+    if (get_key::<bool>("frozen", &key_to_str(account))) {
+        runtime::revert(Error::AccountFrozen);
+    }
+}
+
+fn _has_role(role: &str, account: &Key) -> bool {
+    get_key::<bool>("roles", &role_key(role, account))
+}
+
+fn _grant_role(role: &str, account: Key) {
+    if (!ROLES.contains(&role)) {
+        runtime::revert(Error::InvalidRole);
+    }
+    set_key("roles", &role_key(role, &account), true);
+    emit(Event::RoleGranted { role: role.to_string(), account });
+}
+
+fn _revoke_role(role: &str, account: Key) {
+    if (!ROLES.contains(&role)) {
+        runtime::revert(Error::InvalidRole);
+    }
+    set_key("roles", &role_key(role, &account), false);
+    emit(Event::RoleRevoked { role: role.to_string(), account });
 }
 
 fn ret<T: CLTyped + ToBytes>(value: T) {
@@ -327,8 +1052,32 @@ fn set_key<T: ToBytes + CLTyped>(dictionary_name: &str, key: &str, value: T) {
     storage::dictionary_put(dictionary_seed_uref, key, value)
 }
 
-fn allowance_key(owner: &Key, sender: &Key) -> String {
-    format!("allowances_{}_{}", owner, sender)
+/// `Key` has no `Default`, so an unset governance reverts instead of reading as a zero key.
+fn get_governance(dictionary_name: &str) -> Key {
+    let dictionary_seed_uref = get_dictionary_seed_uref(dictionary_name);
+    storage::dictionary_get(dictionary_seed_uref, "governance").unwrap_or_revert().unwrap_or_revert()
+}
+
+/// Source-chain transaction hashes are hashed so the dictionary item key stays at 64 chars.
+fn tx_hash_key(tx_hash: &str) -> String {
+    hex::encode(blake2b(tx_hash))
+}
+
+/// Role and account are hashed together so the dictionary item key stays at 64 chars.
+fn role_key(role: &str, account: &Key) -> String {
+    hex::encode(blake2b(format!("{}_{}", role, key_to_str(account))))
+}
+
+/// Both keys are hashed from their tagged bytes, so an account and a contract never share an item.
+fn allowance_key(owner: &Key, spender: &Key) -> String {
+    let mut bytes = owner.to_bytes().unwrap_or_revert();
+    bytes.append(&mut spender.to_bytes().unwrap_or_revert());
+    hex::encode(blake2b(bytes))
+}
+
+/// Named key holding a pair's allowance before the `allowances` dictionary existed.
+fn legacy_allowance_key(owner: &Key, spender: &Key) -> String {
+    format!("allowances_{}_{}", owner, spender)
 }
 
 fn endpoint(name: &str, param: Vec<Parameter>, ret: CLType) -> EntryPoint {
@@ -351,22 +1100,24 @@ fn get_caller() -> Key {
             contract_package_hash: _,
             contract_hash: _,
         } => (*account_hash).into(),
+        // Contracts are identified by their package, so governance and roles survive upgrades.
         CallStackElement::StoredContract {
-            contract_package_hash: _,
-            contract_hash,
-        } => (*contract_hash).into(),
+            contract_package_hash,
+            contract_hash: _,
+        } => (*contract_package_hash).into(),
+    }
+}
+
+fn _role_check(role: &str) {
+    if (!_has_role(role, &get_caller())) {
+        runtime::revert(Error::NoAccessRights);
     }
 }
 
 fn _authorization_check() {
     if (
         get_caller() != 
-        Key::Account(
-            get_key::<AccountHash>(
-            "external",
-            "governance"
-            )
-        )
+        get_governance("external")
     ) {
         runtime::revert(Error::NoAccessRights);
     }
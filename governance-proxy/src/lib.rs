@@ -0,0 +1,134 @@
+#![allow(unused_imports)]
+#![allow(unused_parens)]
+#![allow(non_snake_case)]
+
+extern crate alloc;
+
+use alloc::{string::String, vec::Vec};
+use core::convert::TryInto;
+
+use contract::{
+    contract_api::{runtime, storage},
+    unwrap_or_revert::UnwrapOrRevert
+};
+use types::{ApiError, CLType, CLTyped, ContractHash, ContractPackageHash, Key, Parameter, RuntimeArgs, U256, account::AccountHash, contracts::{EntryPoint, EntryPointAccess, EntryPointType, EntryPoints, NamedKeys}, bytesrepr::FromBytes, runtime_args};
+
+pub enum Error {
+    NoAccessRights = 0,
+}
+
+impl From<Error> for ApiError {
+    fn from(error: Error) -> ApiError {
+        ApiError::User(error as u16)
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn mint() {
+    _owner_check();
+    let token: Key = runtime::get_named_arg("token");
+    let owner: Key = runtime::get_named_arg("owner");
+    let amount: U256 = runtime::get_named_arg("amount");
+    runtime::call_contract::<()>(
+        ContractHash::new(token.into_hash().unwrap_or_revert()),
+        "mint",
+        runtime_args! {
+            "owner" => owner,
+            "amount" => amount
+        }
+    );
+}
+
+#[no_mangle]
+pub extern "C" fn apply_governance() {
+    _owner_check();
+    let token: Key = runtime::get_named_arg("token");
+    runtime::call_contract::<()>(
+        ContractHash::new(token.into_hash().unwrap_or_revert()),
+        "apply_governance",
+        runtime_args! {}
+    );
+}
+
+/// Calls any entry point of `contract` with serialized runtime args, e.g. governance actions on the
+/// factory or the cspr holder.
+#[no_mangle]
+pub extern "C" fn execute() {
+    _owner_check();
+    let contract: Key = runtime::get_named_arg("contract");
+    let entry_point: String = runtime::get_named_arg("entry_point");
+    let args: Vec<u8> = runtime::get_named_arg("args");
+    let (args, _) = RuntimeArgs::from_bytes(&args).unwrap_or_revert();
+    runtime::call_contract::<()>(
+        ContractHash::new(contract.into_hash().unwrap_or_revert()),
+        &entry_point,
+        args
+    );
+}
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let mut entry_points = EntryPoints::new();
+    entry_points.add_entry_point(endpoint(
+        "mint",
+        vec![
+            Parameter::new("token", CLType::Key),
+            Parameter::new("owner", CLType::Key),
+            Parameter::new("amount", CLType::U256),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "apply_governance",
+        vec![
+            Parameter::new("token", CLType::Key),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "execute",
+        vec![
+            Parameter::new("contract", CLType::Key),
+            Parameter::new("entry_point", CLType::String),
+            Parameter::new("args", Vec::<u8>::cl_type()),
+        ],
+        CLType::Unit,
+    ));
+
+    let mut named_keys = NamedKeys::new();
+    named_keys.insert(
+        "owner".to_string(),
+        storage::new_uref(runtime::get_caller()).into()
+    );
+
+    let (contract_hash, _) = storage::new_locked_contract(
+        entry_points,
+        Some(named_keys),
+        Some("GovernanceProxy_package".to_string()),
+        None
+    );
+    runtime::put_key("GovernanceProxy", contract_hash.into());
+    runtime::put_key("GovernanceProxy_hash", storage::new_uref(contract_hash).into());
+    // Contracts see the proxy as its package, that is the key to hand governance to.
+    let package_hash = runtime::get_key("GovernanceProxy_package").unwrap_or_revert().into_hash().unwrap_or_revert();
+    runtime::put_key("GovernanceProxy_package_hash", storage::new_uref(ContractPackageHash::new(package_hash)).into());
+}
+
+fn endpoint(name: &str, param: Vec<Parameter>, ret: CLType) -> EntryPoint {
+    EntryPoint::new(
+        String::from(name),
+        param,
+        ret,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    )
+}
+
+/// Only the account that stored the proxy can make it act on a token.
+fn _owner_check() {
+    let owner_uref = runtime::get_key("owner").unwrap_or_revert().try_into().unwrap_or_revert();
+    let owner: AccountHash = storage::read(owner_uref).unwrap_or_revert().unwrap_or_revert();
+    if (runtime::get_caller() != owner) {
+        runtime::revert(Error::NoAccessRights);
+    }
+}
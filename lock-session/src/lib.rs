@@ -0,0 +1,41 @@
+#![allow(unused_imports)]
+#![allow(unused_parens)]
+#![allow(non_snake_case)]
+
+extern crate alloc;
+
+use alloc::string::String;
+
+use contract::{
+    contract_api::{
+        account,
+        runtime,
+        system::{self, transfer_from_purse_to_purse}
+    },
+    unwrap_or_revert::UnwrapOrRevert
+};
+use types::{ContractHash, Key, RuntimeArgs, U256, U512, URef, runtime_args};
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let cspr_holder_hash: Key = runtime::get_named_arg("cspr_holder_hash");
+    let amount: U512 = runtime::get_named_arg("amount");
+    let to_chain_id: U256 = runtime::get_named_arg("to_chain_id");
+    let to_address: String = runtime::get_named_arg("to_address");
+
+    // Fund a temporary purse so the holder never gets access to the caller's main purse.
+    let src_purse: URef = system::create_purse();
+    transfer_from_purse_to_purse(account::get_main_purse(), src_purse, amount, None)
+        .unwrap_or_revert();
+
+    runtime::call_contract::<()>(
+        ContractHash::new(cspr_holder_hash.into_hash().unwrap_or_revert()),
+        "lock",
+        runtime_args! {
+            "src_purse" => src_purse,
+            "amount" => amount,
+            "to_chain_id" => to_chain_id,
+            "to_address" => to_address
+        }
+    );
+}
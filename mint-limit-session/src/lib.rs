@@ -0,0 +1,27 @@
+#![allow(unused_imports)]
+#![allow(unused_parens)]
+#![allow(non_snake_case)]
+
+extern crate alloc;
+
+use contract::{
+    contract_api::{runtime, storage},
+    unwrap_or_revert::UnwrapOrRevert
+};
+use types::{ContractHash, Key, RuntimeArgs, U256, runtime_args};
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let token: Key = runtime::get_named_arg("token");
+    let minter: Key = runtime::get_named_arg("minter");
+
+    let remaining: Option<U256> = runtime::call_contract(
+        ContractHash::new(token.into_hash().unwrap_or_revert()),
+        "remaining_mint_limit",
+        runtime_args! {
+            "minter" => minter
+        }
+    );
+    // Entry point results are not returned to the deployer, so they are kept in its named keys.
+    runtime::put_key("remaining_mint_limit", storage::new_uref(remaining).into());
+}
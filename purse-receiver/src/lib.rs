@@ -0,0 +1,57 @@
+#![allow(unused_imports)]
+#![allow(unused_parens)]
+#![allow(non_snake_case)]
+
+extern crate alloc;
+
+use alloc::string::String;
+
+use contract::{
+    contract_api::{runtime, storage, system},
+    unwrap_or_revert::UnwrapOrRevert
+};
+use types::{CLType, CLValue, ContractPackageHash, Parameter, URef, contracts::{EntryPoint, EntryPointAccess, EntryPointType, EntryPoints, NamedKeys}};
+
+/// Hands out an add-only copy of the purse, enough to pay into it but not to withdraw.
+#[no_mangle]
+pub extern "C" fn receiving_purse() {
+    let purse = *runtime::get_key("purse").unwrap_or_revert().as_uref().unwrap_or_revert();
+    runtime::ret(CLValue::from_t(purse.into_add()).unwrap_or_revert())
+}
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let mut entry_points = EntryPoints::new();
+    entry_points.add_entry_point(endpoint("receiving_purse", vec![], CLType::URef));
+
+    let purse: URef = system::create_purse();
+    let mut named_keys = NamedKeys::new();
+    named_keys.insert(
+        "purse".to_string(),
+        purse.into()
+    );
+
+    let (contract_hash, _) = storage::new_locked_contract(
+        entry_points,
+        Some(named_keys),
+        Some("PurseReceiver_package".to_string()),
+        None
+    );
+    runtime::put_key("PurseReceiver", contract_hash.into());
+    runtime::put_key("PurseReceiver_hash", storage::new_uref(contract_hash).into());
+    // Unlocks pay a contract through its package, which is what the holder calls.
+    let package_hash = runtime::get_key("PurseReceiver_package").unwrap_or_revert().into_hash().unwrap_or_revert();
+    runtime::put_key("PurseReceiver_package_hash", storage::new_uref(ContractPackageHash::new(package_hash)).into());
+    // The deployer can check the balance and pay into the purse directly, but never withdraw.
+    runtime::put_key("PurseReceiver_purse", purse.into_read_add().into());
+}
+
+fn endpoint(name: &str, param: Vec<Parameter>, ret: CLType) -> EntryPoint {
+    EntryPoint::new(
+        String::from(name),
+        param,
+        ret,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    )
+}
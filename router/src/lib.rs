@@ -0,0 +1,428 @@
+#![allow(unused_imports)]
+#![allow(unused_parens)]
+#![allow(non_snake_case)]
+
+extern crate alloc;
+
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    string::String,
+};
+use core::convert::TryInto;
+
+use contract::{
+    contract_api::{
+        runtime::{self, blake2b},
+        storage::{self, create_contract_package_at_hash}
+    },
+    unwrap_or_revert::UnwrapOrRevert
+};
+use types::{ApiError, CLType, CLTyped, CLValue, ContractHash, Key, Parameter, PublicKey, RuntimeArgs, U256, U512, URef, account::AccountHash, bytesrepr::{FromBytes, ToBytes}, contracts::{EntryPoint, EntryPointAccess, EntryPointType, EntryPoints, NamedKeys}, runtime_args, system::CallStackElement};
+
+pub enum Error {
+    NoAccessRights = 0,
+    SwapInAlreadyProcessed = 1,
+    NoPendingGovernance = 2,
+    GovernanceDelayNotPassed = 3,
+}
+
+impl From<Error> for ApiError {
+    fn from(error: Error) -> ApiError {
+        ApiError::User(error as u16)
+    }
+}
+
+/// Delay before a proposed governance can be applied: 2 days, block time being in milliseconds.
+const GOVERNANCE_DELAY: u64 = 172_800_000;
+
+pub enum Event {
+    SwapOut { token: String, from: Key, to: String, amount: U256, to_chain_id: U256 },
+    SwapIn { tx_hash: String, token: String, to: Key, amount: U256, from_chain_id: U256 },
+    NativeSwapOut { from: Key, to: String, amount: U512, to_chain_id: U256 },
+    NativeSwapIn { tx_hash: String, to: Key, amount: U512, from_chain_id: U256 },
+}
+
+impl Event {
+    /// Flattens the event into the string map stored in the `router_events` dictionary.
+    fn into_map(self) -> BTreeMap<String, String> {
+        let mut event = BTreeMap::new();
+        match self {
+            Event::SwapOut { token, from, to, amount, to_chain_id } => {
+                event.insert("event_type".to_string(), "swap_out".to_string());
+                event.insert("token".to_string(), token);
+                event.insert("from".to_string(), from.to_formatted_string());
+                event.insert("to".to_string(), to);
+                event.insert("amount".to_string(), amount.to_string());
+                event.insert("to_chain_id".to_string(), to_chain_id.to_string());
+            }
+            Event::SwapIn { tx_hash, token, to, amount, from_chain_id } => {
+                event.insert("event_type".to_string(), "swap_in".to_string());
+                event.insert("tx_hash".to_string(), tx_hash);
+                event.insert("token".to_string(), token);
+                event.insert("to".to_string(), to.to_formatted_string());
+                event.insert("amount".to_string(), amount.to_string());
+                event.insert("from_chain_id".to_string(), from_chain_id.to_string());
+            }
+            Event::NativeSwapOut { from, to, amount, to_chain_id } => {
+                event.insert("event_type".to_string(), "native_swap_out".to_string());
+                event.insert("from".to_string(), from.to_formatted_string());
+                event.insert("to".to_string(), to);
+                event.insert("amount".to_string(), amount.to_string());
+                event.insert("to_chain_id".to_string(), to_chain_id.to_string());
+            }
+            Event::NativeSwapIn { tx_hash, to, amount, from_chain_id } => {
+                event.insert("event_type".to_string(), "native_swap_in".to_string());
+                event.insert("tx_hash".to_string(), tx_hash);
+                event.insert("to".to_string(), to.to_formatted_string());
+                event.insert("amount".to_string(), amount.to_string());
+                event.insert("from_chain_id".to_string(), from_chain_id.to_string());
+            }
+        }
+        event
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn any_swap_out() {
+    let token: String = runtime::get_named_arg("token");
+    let to: String = runtime::get_named_arg("to");
+    let amount: U256 = runtime::get_named_arg("amount");
+    let to_chain_id: U256 = runtime::get_named_arg("to_chain_id");
+    let from = get_caller();
+    runtime::call_contract::<()>(
+        token_hash(&token),
+        "burn_from",
+        runtime_args! {
+            "owner" => from,
+            "amount" => amount
+        }
+    );
+    emit(Event::SwapOut { token, from, to, amount, to_chain_id });
+}
+
+#[no_mangle]
+pub extern "C" fn any_swap_in() {
+    _authorization_check();
+    let tx_hash: String = runtime::get_named_arg("tx_hash");
+    let token: String = runtime::get_named_arg("token");
+    let to: Key = runtime::get_named_arg("to");
+    let amount: U256 = runtime::get_named_arg("amount");
+    let from_chain_id: U256 = runtime::get_named_arg("from_chain_id");
+    _mark_swap_in(&tx_hash);
+    runtime::call_contract::<()>(
+        token_hash(&token),
+        "mint",
+        runtime_args! {
+            "owner" => to,
+            "amount" => amount
+        }
+    );
+    emit(Event::SwapIn { tx_hash, token, to, amount, from_chain_id });
+}
+
+#[no_mangle]
+pub extern "C" fn any_swap_out_native() {
+    let src_purse: URef = runtime::get_named_arg("src_purse");
+    let to: String = runtime::get_named_arg("to");
+    let amount: U512 = runtime::get_named_arg("amount");
+    let to_chain_id: U256 = runtime::get_named_arg("to_chain_id");
+    let from = get_caller();
+    // The holder credits the deposit to `from`, the router only relays it.
+    runtime::call_contract::<()>(
+        get_key::<ContractHash>("router_data", "cspr_holder_hash"),
+        "lock_for",
+        runtime_args! {
+            "src_purse" => src_purse,
+            "amount" => amount,
+            "to_chain_id" => to_chain_id,
+            "to_address" => to.clone(),
+            "depositor" => from
+        }
+    );
+    emit(Event::NativeSwapOut { from, to, amount, to_chain_id });
+}
+
+#[no_mangle]
+pub extern "C" fn any_swap_in_native() {
+    _authorization_check();
+    let tx_hash: String = runtime::get_named_arg("tx_hash");
+    // An account, a purse or a contract package, whatever the holder can pay out to.
+    let target: Key = runtime::get_named_arg("target");
+    let amount: U512 = runtime::get_named_arg("amount");
+    let from_chain_id: U256 = runtime::get_named_arg("from_chain_id");
+    _mark_swap_in(&tx_hash);
+    // The router is one of the holder's signers, the release still needs the holder's threshold.
+    runtime::call_contract::<()>(
+        get_key::<ContractHash>("router_data", "cspr_holder_hash"),
+        "approve_unlock",
+        runtime_args! {
+            "tx_id" => tx_hash.clone(),
+            "target" => target,
+            "amount" => amount
+        }
+    );
+    emit(Event::NativeSwapIn { tx_hash, to: target, amount, from_chain_id });
+}
+
+#[no_mangle]
+pub extern "C" fn governance() {
+    let val: Key = get_governance("router_data");
+    ret(val)
+}
+
+#[no_mangle]
+pub extern "C" fn pending_governance() {
+    let val: Option<Key> = get_key("router_data", "pending_governance");
+    ret(val)
+}
+
+#[no_mangle]
+pub extern "C" fn propose_governance() {
+    _authorization_check();
+    let new_governance: Key = runtime::get_named_arg("new_governance");
+    let effective_time = u64::from(runtime::get_blocktime()) + GOVERNANCE_DELAY;
+    set_key("router_data", "pending_governance", Some(new_governance));
+    set_key("router_data", "governance_effective_time", effective_time);
+}
+
+#[no_mangle]
+pub extern "C" fn apply_governance() {
+    let pending_governance = get_key::<Option<Key>>("router_data", "pending_governance")
+        .unwrap_or_revert_with(Error::NoPendingGovernance);
+    // The new key has to prove it is usable before it takes over.
+    if (get_caller() != pending_governance) {
+        runtime::revert(Error::NoAccessRights);
+    }
+    if (u64::from(runtime::get_blocktime()) < get_key::<u64>("router_data", "governance_effective_time")) {
+        runtime::revert(Error::GovernanceDelayNotPassed);
+    }
+    set_key("router_data", "governance", pending_governance);
+    set_key("router_data", "pending_governance", None::<Key>);
+}
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let governance: Key = runtime::get_named_arg("governance");
+    let factory_hash: Key = runtime::get_named_arg("factory_hash");
+    let cspr_holder_hash: Key = runtime::get_named_arg("cspr_holder_hash");
+
+    let mut entry_points = EntryPoints::new();
+    entry_points.add_entry_point(endpoint(
+        "any_swap_out",
+        vec![
+            Parameter::new("token", CLType::String),
+            Parameter::new("to", CLType::String),
+            Parameter::new("amount", CLType::U256),
+            Parameter::new("to_chain_id", CLType::U256),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "any_swap_in",
+        vec![
+            Parameter::new("tx_hash", CLType::String),
+            Parameter::new("token", CLType::String),
+            Parameter::new("to", CLType::Key),
+            Parameter::new("amount", CLType::U256),
+            Parameter::new("from_chain_id", CLType::U256),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "any_swap_out_native",
+        vec![
+            Parameter::new("src_purse", CLType::URef),
+            Parameter::new("to", CLType::String),
+            Parameter::new("amount", CLType::U512),
+            Parameter::new("to_chain_id", CLType::U256),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint(
+        "any_swap_in_native",
+        vec![
+            Parameter::new("tx_hash", CLType::String),
+            Parameter::new("target", CLType::Key),
+            Parameter::new("amount", CLType::U512),
+            Parameter::new("from_chain_id", CLType::U256),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint("governance", vec![], CLType::Key));
+    entry_points.add_entry_point(endpoint("pending_governance", vec![], Option::<Key>::cl_type()));
+    entry_points.add_entry_point(endpoint(
+        "propose_governance",
+        vec![
+            Parameter::new("new_governance", CLType::Key),
+        ],
+        CLType::Unit,
+    ));
+    entry_points.add_entry_point(endpoint("apply_governance", vec![], CLType::Unit));
+
+    let dictionary_seed_uref = storage::new_dictionary("router_data").unwrap_or_revert();
+    storage::dictionary_put(
+        dictionary_seed_uref,
+        "governance",
+        governance
+    );
+    storage::dictionary_put(
+        dictionary_seed_uref,
+        "factory_hash",
+        ContractHash::new(factory_hash.into_hash().unwrap_or_revert())
+    );
+    storage::dictionary_put(
+        dictionary_seed_uref,
+        "cspr_holder_hash",
+        ContractHash::new(cspr_holder_hash.into_hash().unwrap_or_revert())
+    );
+    let mut named_keys = NamedKeys::new();
+    named_keys.insert(
+        "router_data".to_string(), 
+        dictionary_seed_uref.into()
+    );
+    named_keys.insert(
+        "router_swap_ins".to_string(),
+        storage::new_dictionary("router_swap_ins").unwrap_or_revert().into()
+    );
+    named_keys.insert(
+        "router_events".to_string(),
+        storage::new_dictionary("router_events").unwrap_or_revert().into()
+    );
+    named_keys.insert(
+        "router_event_count".to_string(),
+        storage::new_uref(0u64).into()
+    );
+
+    let (contract_package_hash, access_uref) = create_contract_package_at_hash();
+    // Add new version to the package.
+    let (contract_hash, _) =
+        storage::add_contract_version(contract_package_hash, entry_points, named_keys);
+    // Save contract and contract hash in the caller's context.
+    runtime::put_key("Router", contract_hash.into());
+    runtime::put_key("Router_hash", storage::new_uref(contract_hash).into());
+    // The package hash is the router's identity towards the tokens and the holder.
+    runtime::put_key("Router_package_hash", storage::new_uref(contract_package_hash).into());
+    // Save access_uref
+    runtime::put_key("router_access_uref", access_uref.into());
+    // Save contract_hash under the contract's dictionary to be accessed through the contract's endpoints.
+    storage::dictionary_put(
+        dictionary_seed_uref,
+        "contract_hash",
+        contract_hash,
+    );
+}
+
+/// Resolves a token name through the factory's `tokens` registry, reverting for unknown tokens.
+fn token_hash(token: &str) -> ContractHash {
+    runtime::call_contract(
+        get_key::<ContractHash>("router_data", "factory_hash"),
+        "get_erc20_hash",
+        runtime_args! {
+            "token_name" => token.to_string()
+        }
+    )
+}
+
+/// Source-chain transactions, token or native, can only ever be swapped in once.
+fn _mark_swap_in(tx_hash: &str) {
+    let swap_key = hex::encode(blake2b(tx_hash));
+    if (get_key::<bool>("router_swap_ins", &swap_key)) {
+        runtime::revert(Error::SwapInAlreadyProcessed);
+    }
+    set_key("router_swap_ins", &swap_key, true);
+}
+
+/// Appends an event to the `router_events` dictionary under the next sequence number.
+fn emit(event: Event) {
+    let index: u64 = get_key_runtime("router_event_count");
+    set_key("router_events", &index.to_string(), event.into_map());
+    set_key_runtime("router_event_count", index + 1);
+}
+
+fn get_dictionary_seed_uref(name: &str) -> URef {
+    let dictionary_seed_uref = match runtime::get_key(name) {
+        Some(key) => key.into_uref().unwrap_or_revert(),
+        None => {
+            let new_dict = storage::new_dictionary(name).unwrap_or_revert();
+            let key = storage::new_uref(new_dict).into();
+            runtime::put_key(name, key);
+            new_dict
+        },
+    };
+    dictionary_seed_uref
+}
+
+fn get_key_runtime<T: FromBytes + CLTyped + Default>(name: &str) -> T {
+    match runtime::get_key(name) {
+        None => Default::default(),
+        Some(value) => {
+            let key = value.try_into().unwrap_or_revert();
+            storage::read(key).unwrap_or_revert().unwrap_or_revert()
+        }
+    }
+}
+
+fn set_key_runtime<T: ToBytes + CLTyped>(name: &str, value: T) {
+    match runtime::get_key(name) {
+        Some(key) => {
+            let key_ref = key.try_into().unwrap_or_revert();
+            storage::write(key_ref, value);
+        }
+        None => {
+            let key = storage::new_uref(value).into();
+            runtime::put_key(name, key);
+        }
+    }
+}
+
+fn get_key<T: FromBytes + CLTyped + Default>(dictionary_name: &str, key: &str) -> T {
+    let dictionary_seed_uref = get_dictionary_seed_uref(dictionary_name);
+    storage::dictionary_get(dictionary_seed_uref, key).unwrap_or_default().unwrap_or_default()
+}
+
+fn set_key<T: ToBytes + CLTyped>(dictionary_name: &str, key: &str, value: T) {
+    let dictionary_seed_uref = get_dictionary_seed_uref(dictionary_name);
+    storage::dictionary_put(dictionary_seed_uref, key, value)
+}
+
+/// `Key` has no `Default`, so an unset governance reverts instead of reading as a zero key.
+fn get_governance(dictionary_name: &str) -> Key {
+    let dictionary_seed_uref = get_dictionary_seed_uref(dictionary_name);
+    storage::dictionary_get(dictionary_seed_uref, "governance").unwrap_or_revert().unwrap_or_revert()
+}
+
+fn endpoint(name: &str, param: Vec<Parameter>, ret: CLType) -> EntryPoint {
+    EntryPoint::new(
+        String::from(name),
+        param,
+        ret,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    )
+}
+
+fn get_caller() -> Key {
+    let mut callstack = runtime::get_call_stack();
+    callstack.pop();
+    match callstack.last().unwrap_or_revert() {
+        CallStackElement::Session { account_hash } => (*account_hash).into(),
+        CallStackElement::StoredSession {
+            account_hash,
+            contract_package_hash: _,
+            contract_hash: _,
+        } => (*account_hash).into(),
+        CallStackElement::StoredContract {
+            contract_package_hash,
+            contract_hash: _,
+        } => (*contract_package_hash).into(),
+    }
+}
+
+fn _authorization_check() {
+    if (
+        get_caller() != 
+        get_governance("router_data")
+    ) {
+        runtime::revert(Error::NoAccessRights);
+    }
+}
@@ -19,6 +19,7 @@ FACTORY_HASH=hash-4bc5f6d0cfdcb2c8ceb54abfbf370a6f3c229ab89d101a9109ca9a43f45742
 ERC20_SESSION_PATH=./target/wasm32-unknown-unknown/release/erc20.wasm
 CSPR_HOLDER_SESSION_PATH=./target/wasm32-unknown-unknown/release/cspr_holder.wasm
 FACTORY_SESSION_PATH=./target/wasm32-unknown-unknown/release/factory.wasm
+LOCK_SESSION_PATH=./target/wasm32-unknown-unknown/release/lock_session.wasm
 GOVERNANCE_KEY=./keys/governance/secret_key.pem
 ERC20_KEY=./keys/erc20/secret_key.pem
 CSPR_HOLDER_KEY=./keys/cspr_holder/secret_key.pem
@@ -36,7 +37,7 @@ then
     pushd ./erc20
     cargo build --release
     popd
-    casper-client put-deploy --chain-name casper-test --node-address ${NODE_ADDRESS} --payment-amount ${DEPLOY_AMOUNT} --session-path ${ERC20_SESSION_PATH} --secret-key ${ERC20_KEY} --session-arg "token_name:string='ERC20'" "token_symbol:string='ERC'" "token_decimals:u8='8'" "token_total_supply:u256='1000'" "governance:account_hash='$3'"
+    casper-client put-deploy --chain-name casper-test --node-address ${NODE_ADDRESS} --payment-amount ${DEPLOY_AMOUNT} --session-path ${ERC20_SESSION_PATH} --secret-key ${ERC20_KEY} --session-arg "token_name:string='ERC20'" "token_symbol:string='ERC'" "token_decimals:u8='8'" "token_total_supply:u256='1000'" "token_max_supply:opt_u256=null" "governance:key='$3'"
   elif [[ $2 == 'query' ]]
   then
     if [[ $3 == '' ]]
@@ -107,34 +108,47 @@ then
         pushd ./cspr-holder
         cargo build --release
         popd
-        casper-client put-deploy --chain-name casper-test --node-address ${NODE_ADDRESS} --payment-amount ${DEPLOY_AMOUNT} --session-path ${CSPR_HOLDER_SESSION_PATH} --secret-key ${CSPR_HOLDER_KEY} --session-arg "governance:account_hash='$3'"
+        casper-client put-deploy --chain-name casper-test --node-address ${NODE_ADDRESS} --payment-amount ${DEPLOY_AMOUNT} --session-path ${CSPR_HOLDER_SESSION_PATH} --secret-key ${CSPR_HOLDER_KEY} --session-arg "governance:key='$3'"
     else
         echo "${red_bg}↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴ Invalid Syntax! ↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴${reset}"
         echo "[✔] ${red}cspr_holder ${purple}deploy${reset} <GOVERNANCE>"
         exit 0
     fi
+  elif [[ $2 == 'deposit' ]]
+  then
+    if [[ $3 != '' && $4 != '' && $5 != '' ]]
+    then
+        pushd ./lock-session
+        cargo build --release
+        popd
+        casper-client put-deploy --chain-name casper-test --node-address ${NODE_ADDRESS} --payment-amount ${QUERY_AMOUNT} --session-path ${LOCK_SESSION_PATH} --secret-key ${ERC20_KEY} --session-arg "cspr_holder_hash:key='${CSPR_HOLDER_HASH}'" "amount:u512='$3'" "to_chain_id:u256='$4'" "to_address:string='$5'"
+    else
+        echo "${red_bg}↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴ Invalid Syntax! ↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴${reset}"
+        echo "[✔] ${red}cspr_holder ${purple}deposit${reset} <AMOUNT> <TO_CHAIN_ID> <TO_ADDRESS>"
+        exit 0
+    fi
   elif [[ $2 == 'query' ]]
   then
     #   ====Getters (endpoints that return data using ret()) will generate a Return Error when being queried on testnet.====
     #   ====They were tested locally and since the tests on testnet went smoothly, they should work perfectly.====
     if [[ $3 == 'lock' ]]
     then
-      if [[ $4 != '' && $5 != '' ]]
+      if [[ $4 != '' && $5 != '' && $6 != '' && $7 != '' ]]
       then
-        casper-client put-deploy --chain-name casper-test --node-address ${NODE_ADDRESS} --payment-amount ${QUERY_AMOUNT} --secret-key ${ERC20_KEY} --session-hash ${CSPR_HOLDER_HASH} --session-entry-point lock --session-arg "src_purse:uref='$4'" "amount:u512='$5'"
+        casper-client put-deploy --chain-name casper-test --node-address ${NODE_ADDRESS} --payment-amount ${QUERY_AMOUNT} --secret-key ${ERC20_KEY} --session-hash ${CSPR_HOLDER_HASH} --session-entry-point lock --session-arg "src_purse:uref='$4'" "amount:u512='$5'" "to_chain_id:u256='$6'" "to_address:string='$7'"
       else
         echo "${red_bg}↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴ Invalid Syntax! ↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴${reset}"
-        echo "[✔] ${red}cspr_holder ${purple}query ${green}lock${reset} <SRC_PURSE> <AMOUNT>"
+        echo "[✔] ${red}cspr_holder ${purple}query ${green}lock${reset} <SRC_PURSE> <AMOUNT> <TO_CHAIN_ID> <TO_ADDRESS>"
         exit 0
       fi
-    elif [[ $3 == 'unlock' ]]
+    elif [[ $3 == 'approve_unlock' ]]
     then
-      if [[ $4 != '' && $5 != '' ]]
+      if [[ $4 != '' && $5 != '' && $6 != '' ]]
       then
-        casper-client put-deploy --chain-name casper-test --node-address ${NODE_ADDRESS} --payment-amount ${QUERY_AMOUNT} --secret-key ${GOVERNANCE_KEY} --session-hash ${CSPR_HOLDER_HASH} --session-entry-point unlock --session-arg "target_pubkey:public_key='$4'" "amount:u512='$5'"
+        casper-client put-deploy --chain-name casper-test --node-address ${NODE_ADDRESS} --payment-amount ${QUERY_AMOUNT} --secret-key ${GOVERNANCE_KEY} --session-hash ${CSPR_HOLDER_HASH} --session-entry-point approve_unlock --session-arg "tx_id:string='$4'" "target:key='$5'" "amount:u512='$6'"
       else
         echo "${red_bg}↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴ Invalid Syntax! ↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴${reset}"
-        echo "[✔] ${red}cspr_holder ${purple}query ${green}unlock${reset} <TARGET_PUBKEY> <AMOUNT>"
+        echo "[✔] ${red}cspr_holder ${purple}query ${green}approve_unlock${reset} <TX_ID> <TARGET_KEY> <AMOUNT>"
         exit 0
       fi
     fi
@@ -148,7 +162,7 @@ then
         pushd ./factory
         cargo build --release
         popd
-        casper-client put-deploy --chain-name casper-test --node-address ${NODE_ADDRESS} --payment-amount ${DEPLOY_AMOUNT} --session-path ${FACTORY_SESSION_PATH} --secret-key ${FACTORY_KEY} --session-arg "governance:account_hash='$3'"
+        casper-client put-deploy --chain-name casper-test --node-address ${NODE_ADDRESS} --payment-amount ${DEPLOY_AMOUNT} --session-path ${FACTORY_SESSION_PATH} --secret-key ${FACTORY_KEY} --session-arg "governance:key='$3'"
     else
         echo "${red_bg}↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴ Invalid Syntax! ↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴${reset}"
         echo "[✔] ${red}factory ${purple}deploy${reset} <GOVERNANCE>"
@@ -160,7 +174,7 @@ then
     then
       if [[ $4 != '' && $5 != '' && $6 != '' && $7 != '' && $8 != '' ]]
       then
-        casper-client put-deploy --chain-name casper-test --node-address ${NODE_ADDRESS} --payment-amount ${DEPLOY_AMOUNT} --secret-key ${GOVERNANCE_KEY} --session-hash ${FACTORY_HASH} --session-entry-point create_erc20 --session-arg "token_name:string='$4'" "token_symbol:string='$5'" "token_decimals:u8='$6'" "token_total_supply:u256='$7'" "governance:account_hash='$8'"
+        casper-client put-deploy --chain-name casper-test --node-address ${NODE_ADDRESS} --payment-amount ${DEPLOY_AMOUNT} --secret-key ${GOVERNANCE_KEY} --session-hash ${FACTORY_HASH} --session-entry-point create_erc20 --session-arg "token_name:string='$4'" "token_symbol:string='$5'" "token_decimals:u8='$6'" "token_total_supply:u256='$7'" "token_max_supply:opt_u256=null" "governance:key='$8'"
       else
         echo "${red_bg}↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴ Invalid Syntax! ↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴↴${reset}"
         echo "[✔] ${red}factory ${purple}query ${green}create_erc20${reset} <NAME> <SYMBOL> <DECIMALS> <TOTAL_SUPPLY> <GOVERNANCE>"
@@ -207,8 +221,9 @@ then
   echo "[✔] ${red}erc20 ${purple}query${reset} ${green}mint${reset} <OWNER> <AMOUNT>"
   echo "[✔] ${red}erc20 ${purple}query${reset} ${green}burn${reset} <OWNER> <AMOUNT>"
   echo "[✔] ${red}cspr_holder ${purple}deploy${reset} <GOVERNANCE>"
-  echo "[✔] ${red}cspr_holder ${purple}query ${green}lock${reset} <SOURCE_PURSE> <AMOUNT>"
-  echo "[✔] ${red}cspr_holder ${purple}query ${green}unlock${reset} <TARGET_PUBKEY> <AMOUNT>"
+  echo "[✔] ${red}cspr_holder ${purple}deposit${reset} <AMOUNT> <TO_CHAIN_ID> <TO_ADDRESS>"
+  echo "[✔] ${red}cspr_holder ${purple}query ${green}lock${reset} <SOURCE_PURSE> <AMOUNT> <TO_CHAIN_ID> <TO_ADDRESS>"
+  echo "[✔] ${red}cspr_holder ${purple}query ${green}approve_unlock${reset} <TX_ID> <TARGET_KEY> <AMOUNT>"
   echo "[✔] ${red}factory ${purple}deploy${reset} <GOVERNANCE>"
   echo "[✔] ${red}factory ${purple}query ${green}create_erc20${reset} <NAME> <SYMBOL> <DECIMALS> <TOTAL_SUPPLY> <GOVERNANCE>"
 fi
\ No newline at end of file
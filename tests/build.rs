@@ -1,15 +1,21 @@
 use std::{env, fs, path::PathBuf, process::Command};
 
 // Build system
-const CONTRACT_ROOT: [&str; 3] = ["../erc20", "../cspr-holder", "../factory"];
-const CONTRACT_CARGO_TOML: [&str; 3] = ["../erc20/Cargo.toml", "../cspr-holder/Cargo.toml", "../factory/Cargo.toml"];
-const CONTRACT_LIB_RS: [&str; 3] = ["../erc20/src/lib.rs", "../cspr-holder/src/lib.rs", "../factory/src/lib.rs"];
-const BUILD_ARGS: [[&str; 4]; 3] = [
+const CONTRACT_ROOT: [&str; 9] = ["../erc20", "../cspr-holder", "../factory", "../lock-session", "../router", "../governance-proxy", "../purse-receiver", "../erc20-vault", "../mint-limit-session"];
+const CONTRACT_CARGO_TOML: [&str; 9] = ["../erc20/Cargo.toml", "../cspr-holder/Cargo.toml", "../factory/Cargo.toml", "../lock-session/Cargo.toml", "../router/Cargo.toml", "../governance-proxy/Cargo.toml", "../purse-receiver/Cargo.toml", "../erc20-vault/Cargo.toml", "../mint-limit-session/Cargo.toml"];
+const CONTRACT_LIB_RS: [&str; 9] = ["../erc20/src/lib.rs", "../cspr-holder/src/lib.rs", "../factory/src/lib.rs", "../lock-session/src/lib.rs", "../router/src/lib.rs", "../governance-proxy/src/lib.rs", "../purse-receiver/src/lib.rs", "../erc20-vault/src/lib.rs", "../mint-limit-session/src/lib.rs"];
+const BUILD_ARGS: [[&str; 4]; 9] = [
     ["build", "--release", "-p", "erc20"],
     ["build", "--release", "-p", "cspr-holder"],
     ["build", "--release", "-p", "factory"],
+    ["build", "--release", "-p", "lock-session"],
+    ["build", "--release", "-p", "router"],
+    ["build", "--release", "-p", "governance-proxy"],
+    ["build", "--release", "-p", "purse-receiver"],
+    ["build", "--release", "-p", "erc20-vault"],
+    ["build", "--release", "-p", "mint-limit-session"],
 ];
-const WASM_FILENAME: [&str; 3] = ["erc20.wasm", "cspr_holder.wasm", "factory.wasm"];
+const WASM_FILENAME: [&str; 9] = ["erc20.wasm", "cspr_holder.wasm", "factory.wasm", "lock_session.wasm", "router.wasm", "governance_proxy.wasm", "purse_receiver.wasm", "erc20_vault.wasm", "mint_limit_session.wasm"];
 const ORIGINAL_WASM_DIR: &str = "../target/wasm32-unknown-unknown/release";
 const NEW_WASM_DIR: &str = "wasm";
 
@@ -1,5 +1,9 @@
+use std::collections::BTreeMap;
+
 use casper_engine_test_support::{Code, Hash, SessionBuilder, TestContext, TestContextBuilder};
-use casper_types::{AsymmetricType, PublicKey, RuntimeArgs, U512, URef, account::AccountHash, runtime_args};
+use casper_types::{AsymmetricType, CLTyped, Key, PublicKey, RuntimeArgs, U256, U512, URef, account::AccountHash, bytesrepr::{FromBytes, ToBytes}, runtime_args};
+
+use crate::erc20::key_to_str;
 
 // contains methods that can simulate a real-world deployment (storing the contract in the blockchain)
 // and transactions to invoke the methods in the contract.
@@ -8,6 +12,7 @@ pub struct Sender(pub AccountHash);
 
 pub struct CsprHolder {
     context: TestContext,
+    block_time: u64,
     pub ali: AccountHash,
     pub bob: AccountHash,
     pub joe: AccountHash,
@@ -22,10 +27,11 @@ impl CsprHolder {
         let mut context = TestContextBuilder::new()
             .with_public_key(ali.clone(), U512::from(500_000_000_000_000_000u64))
             .with_public_key(bob.clone(), U512::from(500_000_000_000_000_000u64))
+            .with_public_key(joe.clone(), U512::from(500_000_000_000_000_000u64))
             .build();
         let session_code = Code::from("cspr_holder.wasm");
         let session_args = runtime_args! {
-            "governance" => ali.to_account_hash()
+            "governance" => Key::Account(ali.to_account_hash())
         };
         let session = SessionBuilder::new(session_code, session_args)
             .with_address(ali.to_account_hash())
@@ -34,6 +40,7 @@ impl CsprHolder {
         context.run(session);
         CsprHolder {
             context,
+            block_time: 0,
             ali: ali.to_account_hash(),
             bob: bob.to_account_hash(),
             joe: joe.to_account_hash(),
@@ -48,6 +55,43 @@ impl CsprHolder {
             .unwrap_or_else(|_| panic!("{} has wrong type", "CSPR_Holder"))
     }
 
+    pub fn main_purse(&self, account: AccountHash) -> URef {
+        self.context
+            .main_purse_address(account)
+            .unwrap_or_else(|| panic!("{} has no main purse", account))
+    }
+
+    pub fn purse_balance(&self, purse: URef) -> U512 {
+        self.context.get_balance(purse.addr())
+    }
+
+    /// an account's main purse balance, zero if the account doesn't exist yet.
+    pub fn balance_of(&self, account: AccountHash) -> U512 {
+        self.context
+            .main_purse_address(account)
+            .map(|purse| self.purse_balance(purse))
+            .unwrap_or_default()
+    }
+
+    /// query a contract's dictionary's key.
+    fn query_contract_dictionary<T: CLTyped + FromBytes>(
+        &self,
+        key: AccountHash,
+        context: &TestContext,
+        dictionary_name: String,
+        name: String,
+    ) -> Option<T> {
+        match context.query_dictionary_item(key.into(), Some(dictionary_name), name.clone()) {
+            Err(_) => None,
+            Ok(maybe_value) => {
+                let value = maybe_value
+                    .into_t()
+                    .unwrap_or_else(|_| panic!("{} is not the expected type.", name));
+                Some(value)
+            }
+        }
+    }
+
     /// call a contract's specific entry point.
     fn call(&mut self, sender: Sender, method: &str, args: RuntimeArgs) {
         let Sender(address) = sender;
@@ -55,14 +99,145 @@ impl CsprHolder {
         let session = SessionBuilder::new(code, args)
             .with_address(address)
             .with_authorization_keys(&[address])
+            .with_block_time(self.block_time)
+            .build();
+        self.context.run(session);
+    }
+
+    /// moves the block time used by the following calls forward.
+    pub fn advance_block_time(&mut self, millis: u64) {
+        self.block_time += millis;
+    }
+
+    /// stores a governance-proxy contract owned by the sender and returns its hash,
+    /// along with the package key the holder sees it as.
+    pub fn deploy_governance_proxy(&mut self, sender: Sender) -> (Hash, Key) {
+        let Sender(address) = sender;
+        let session = SessionBuilder::new(Code::from("governance_proxy.wasm"), runtime_args! {})
+            .with_address(address)
+            .with_authorization_keys(&[address])
+            .with_block_time(self.block_time)
             .build();
         self.context.run(session);
+        let contract_hash = self.context
+            .query(address, &["GovernanceProxy_hash".to_string()])
+            .unwrap_or_else(|_| panic!("GovernanceProxy contract not found"))
+            .into_t()
+            .unwrap_or_else(|_| panic!("GovernanceProxy has wrong type"));
+        let package_hash: Hash = self.context
+            .query(address, &["GovernanceProxy_package_hash".to_string()])
+            .unwrap_or_else(|_| panic!("GovernanceProxy package not found"))
+            .into_t()
+            .unwrap_or_else(|_| panic!("GovernanceProxy package has wrong type"));
+        (contract_hash, Key::Hash(package_hash))
+    }
+
+    /// makes a governance proxy call one of the holder's entry points.
+    pub fn proxy_execute(&mut self, proxy: Hash, method: &str, args: RuntimeArgs, sender: Sender) {
+        let Sender(address) = sender;
+        let proxy_args = runtime_args! {
+            "contract" => Key::Hash(self.contract_hash()),
+            "entry_point" => method.to_string(),
+            "args" => args.to_bytes().unwrap()
+        };
+        let session = SessionBuilder::new(Code::Hash(proxy, "execute".to_string()), proxy_args)
+            .with_address(address)
+            .with_authorization_keys(&[address])
+            .with_block_time(self.block_time)
+            .build();
+        self.context.run(session);
+    }
+
+    pub fn deposit_count(&self) -> u64 {
+        self.query_contract_dictionary(
+            self.ali,
+            &self.context,
+            "cspr_holder_data".to_string(),
+            "deposit_count".to_string()
+        ).unwrap_or_default()
+    }
+
+    pub fn deposit(&self, nonce: u64) -> BTreeMap<String, String> {
+        self.query_contract_dictionary(
+            self.ali,
+            &self.context,
+            "deposits".to_string(),
+            nonce.to_string()
+        ).unwrap()
+    }
+
+    pub fn total_locked(&self) -> U512 {
+        self.query_contract_dictionary(
+            self.ali,
+            &self.context,
+            "cspr_holder_data".to_string(),
+            "total_locked".to_string()
+        ).unwrap_or_default()
+    }
+
+    pub fn total_unlocked(&self) -> U512 {
+        self.query_contract_dictionary(
+            self.ali,
+            &self.context,
+            "cspr_holder_data".to_string(),
+            "total_unlocked".to_string()
+        ).unwrap_or_default()
+    }
+
+    pub fn deposits_of(&self, account: Key) -> U512 {
+        self.query_contract_dictionary(
+            self.ali,
+            &self.context,
+            "deposits_by_account".to_string(),
+            key_to_str(&account)
+        ).unwrap_or_default()
+    }
+
+    /// the read-only copy of the contract purse saved in the deployer's named keys.
+    pub fn contract_purse(&self) -> URef {
+        self.context
+            .query(self.ali, &["CSPR_Holder_purse".to_string()])
+            .unwrap_or_else(|_| panic!("{} not found", "CSPR_Holder_purse"))
+            .into_t()
+            .unwrap_or_else(|_| panic!("{} has wrong type", "CSPR_Holder_purse"))
+    }
+
+    pub fn reconcile(&mut self, sender: Sender) {
+        self.call(sender, "reconcile", runtime_args! {})
+    }
+
+    pub fn event(&self, index: u64) -> BTreeMap<String, String> {
+        self.query_contract_dictionary(
+            self.ali,
+            &self.context,
+            "cspr_holder_events".to_string(),
+            index.to_string()
+        ).unwrap()
+    }
+
+    pub fn paused(&self) -> bool {
+        self.query_contract_dictionary(
+            self.ali,
+            &self.context,
+            "cspr_holder_data".to_string(),
+            "paused".to_string()
+        ).unwrap_or_default()
+    }
+
+    pub fn pause(&mut self, sender: Sender) {
+        self.call(sender, "pause", runtime_args! {})
+    }
+
+    pub fn unpause(&mut self, sender: Sender) {
+        self.call(sender, "unpause", runtime_args! {})
     }
 
     pub fn lock(
         &mut self,
         src_purse: URef,
         amount: U512,
+        to_chain_id: U256,
+        to_address: &str,
         sender: Sender,
     ) {
         self.call(
@@ -70,24 +245,206 @@ impl CsprHolder {
             "lock",
             runtime_args! {
                 "src_purse" => src_purse,
-                "amount" => amount
+                "amount" => amount,
+                "to_chain_id" => to_chain_id,
+                "to_address" => to_address.to_string()
             }
         )
     }
 
-    pub fn unlock(
+    /// lock CSPR from the sender's main purse through the `lock_session.wasm` session code.
+    pub fn lock_with_session(
         &mut self,
+        amount: U512,
+        to_chain_id: U256,
+        to_address: &str,
+        sender: Sender,
+    ) {
+        let Sender(address) = sender;
+        let session_code = Code::from("lock_session.wasm");
+        let session_args = runtime_args! {
+            "cspr_holder_hash" => Key::Hash(self.contract_hash()),
+            "amount" => amount,
+            "to_chain_id" => to_chain_id,
+            "to_address" => to_address.to_string()
+        };
+        let session = SessionBuilder::new(session_code, session_args)
+            .with_address(address)
+            .with_authorization_keys(&[address])
+            .build();
+        self.context.run(session);
+    }
+
+    pub fn approve_unlock(
+        &mut self,
+        tx_id: &str,
         target_pubkey: PublicKey,
         amount: U512,
         sender: Sender,
+    ) {
+        self.approve_unlock_to(tx_id, Key::Account(target_pubkey.to_account_hash()), amount, sender)
+    }
+
+    /// approve an unlock paying any supported target: an account, a purse or a contract.
+    pub fn approve_unlock_to(
+        &mut self,
+        tx_id: &str,
+        target: Key,
+        amount: U512,
+        sender: Sender,
     ) {
         self.call(
             sender,
-            "unlock",
+            "approve_unlock",
             runtime_args! {
-                "target_pubkey" => target_pubkey,
+                "tx_id" => tx_id.to_string(),
+                "target" => target,
                 "amount" => amount
             }
         )
     }
-}
\ No newline at end of file
+
+    /// store a `purse-receiver` contract, returning its package key and a read/add copy of its purse.
+    pub fn deploy_purse_receiver(&mut self, sender: Sender) -> (Key, URef) {
+        let Sender(address) = sender;
+        let session = SessionBuilder::new(Code::from("purse_receiver.wasm"), runtime_args! {})
+            .with_address(address)
+            .with_authorization_keys(&[address])
+            .build();
+        self.context.run(session);
+        let package_hash: Hash = self.context
+            .query(address, &["PurseReceiver_package_hash".to_string()])
+            .unwrap_or_else(|_| panic!("PurseReceiver package not found"))
+            .into_t()
+            .unwrap_or_else(|_| panic!("PurseReceiver package has wrong type"));
+        let purse = self.context
+            .query(address, &["PurseReceiver_purse".to_string()])
+            .unwrap_or_else(|_| panic!("PurseReceiver_purse not found"))
+            .into_t()
+            .unwrap_or_else(|_| panic!("PurseReceiver_purse has wrong type"));
+        (Key::Hash(package_hash), purse)
+    }
+
+    pub fn execute_unlock(&mut self, tx_id: &str, sender: Sender) {
+        self.call(
+            sender,
+            "execute_unlock",
+            runtime_args! {
+                "tx_id" => tx_id.to_string()
+            }
+        )
+    }
+
+    pub fn cancel_unlock(&mut self, tx_id: &str, sender: Sender) {
+        self.call(
+            sender,
+            "cancel_unlock",
+            runtime_args! {
+                "tx_id" => tx_id.to_string()
+            }
+        )
+    }
+
+    pub fn set_unlock_limits(
+        &mut self,
+        min_unlock: U512,
+        max_unlock: Option<U512>,
+        daily_unlock_cap: Option<U512>,
+        large_unlock_threshold: Option<U512>,
+        sender: Sender,
+    ) {
+        self.call(
+            sender,
+            "set_unlock_limits",
+            runtime_args! {
+                "min_unlock" => min_unlock,
+                "max_unlock" => max_unlock,
+                "daily_unlock_cap" => daily_unlock_cap,
+                "large_unlock_threshold" => large_unlock_threshold
+            }
+        )
+    }
+
+    pub fn set_refund_timeout(&mut self, refund_timeout: Option<u64>, sender: Sender) {
+        self.call(
+            sender,
+            "set_refund_timeout",
+            runtime_args! {
+                "refund_timeout" => refund_timeout
+            }
+        )
+    }
+
+    pub fn complete_deposit(&mut self, deposit_id: u64, sender: Sender) {
+        self.call(
+            sender,
+            "complete_deposit",
+            runtime_args! {
+                "deposit_id" => deposit_id
+            }
+        )
+    }
+
+    pub fn refund(&mut self, deposit_id: u64, sender: Sender) {
+        self.call(
+            sender,
+            "refund",
+            runtime_args! {
+                "deposit_id" => deposit_id
+            }
+        )
+    }
+
+    pub fn add_signer(&mut self, signer: Key, sender: Sender) {
+        self.call(
+            sender,
+            "add_signer",
+            runtime_args! {
+                "signer" => signer
+            }
+        )
+    }
+
+    pub fn remove_signer(&mut self, signer: Key, sender: Sender) {
+        self.call(
+            sender,
+            "remove_signer",
+            runtime_args! {
+                "signer" => signer
+            }
+        )
+    }
+
+    pub fn set_threshold(&mut self, threshold: u32, sender: Sender) {
+        self.call(
+            sender,
+            "set_threshold",
+            runtime_args! {
+                "threshold" => threshold
+            }
+        )
+    }
+
+    pub fn governance(&self) -> Key {
+        self.query_contract_dictionary(
+            self.ali,
+            &self.context,
+            "cspr_holder_data".to_string(),
+            "governance".to_string()
+        ).unwrap()
+    }
+
+    pub fn propose_governance(&mut self, new_governance: Key, sender: Sender) {
+        self.call(
+            sender,
+            "propose_governance",
+            runtime_args! {
+                "new_governance" => new_governance
+            }
+        )
+    }
+
+    pub fn apply_governance(&mut self, sender: Sender) {
+        self.call(sender, "apply_governance", runtime_args! {})
+    }
+}
@@ -1,5 +1,8 @@
+use std::collections::BTreeMap;
+
+use blake2::{VarBlake2b, digest::{Update, VariableOutput}};
 use casper_engine_test_support::{Code, Hash, SessionBuilder, TestContext, TestContextBuilder};
-use casper_types::{AsymmetricType, CLTyped, Key, PublicKey, RuntimeArgs, U256, U512, account::AccountHash, bytesrepr::FromBytes, runtime_args};
+use casper_types::{AsymmetricType, CLTyped, Key, PublicKey, RuntimeArgs, U256, U512, account::AccountHash, bytesrepr::{FromBytes, ToBytes}, runtime_args};
 
 // contains methods that can simulate a real-world deployment (storing the contract in the blockchain)
 // and transactions to invoke the methods in the contract.
@@ -20,12 +23,13 @@ pub struct Token {
     pub name: String,
     pub symbol: String,
     context: TestContext,
+    block_time: u64,
     pub ali: AccountHash,
     pub bob: AccountHash,
     pub joe: AccountHash,
 }
 
-fn key_to_str(key: &Key) -> String {
+pub fn key_to_str(key: &Key) -> String {
     match key {
         Key::Account(account) => account.to_string(),
         Key::Hash(package) => hex::encode(package),
@@ -33,8 +37,26 @@ fn key_to_str(key: &Key) -> String {
     }
 }
 
+/// hex encoded 32 bytes blake2b digest, as computed by the contracts for dictionary item keys.
+pub fn blake2b_hex<T: AsRef<[u8]>>(data: T) -> String {
+    let mut hasher = VarBlake2b::new(32).unwrap();
+    hasher.update(data);
+    let mut digest = [0u8; 32];
+    hasher.finalize_variable(|hash| digest.copy_from_slice(hash));
+    hex::encode(digest)
+}
+
+/// the `roles` dictionary item key of a role held by an account.
+pub fn role_key(role: &str, account: &Key) -> String {
+    blake2b_hex(format!("{}_{}", role, key_to_str(account)))
+}
+
 impl Token {
     pub fn deployed(name: &str, symbol: &str) -> Token {
+        Token::deployed_with_cap(name, symbol, None)
+    }
+
+    pub fn deployed_with_cap(name: &str, symbol: &str, max_supply: Option<U256>) -> Token {
         let ali = PublicKey::ed25519_from_bytes([3u8; 32]).unwrap();
         let bob = PublicKey::ed25519_from_bytes([6u8; 32]).unwrap();
         let joe = PublicKey::ed25519_from_bytes([9u8; 32]).unwrap();
@@ -49,7 +71,8 @@ impl Token {
             "token_symbol" => symbol,
             "token_decimals" => token_cfg::DECIMALS,
             "token_total_supply" => token_cfg::total_supply(),
-            "governance" => ali.to_account_hash()
+            "token_max_supply" => max_supply,
+            "governance" => Key::Account(ali.to_account_hash())
         };
         let session = SessionBuilder::new(session_code, session_args)
             .with_address((&ali).to_account_hash())
@@ -60,6 +83,7 @@ impl Token {
             name: name.to_string(),
             symbol: symbol.to_string(),
             context,
+            block_time: 0,
             ali: ali.to_account_hash(),
             bob: bob.to_account_hash(),
             joe: joe.to_account_hash(),
@@ -116,10 +140,16 @@ impl Token {
         let session = SessionBuilder::new(code, args)
             .with_address(address)
             .with_authorization_keys(&[address])
+            .with_block_time(self.block_time)
             .build();
         self.context.run(session);
     }
 
+    /// moves the block time used by the following calls forward.
+    pub fn advance_block_time(&mut self, millis: u64) {
+        self.block_time += millis;
+    }
+
     pub fn name(&self) -> String {
         self.query_contract_dictionary(
             self.ali,
@@ -156,6 +186,25 @@ impl Token {
         ).unwrap()
     }
 
+    pub fn cap(&self) -> Option<U256> {
+        self.query_contract_dictionary(
+            self.ali,
+            &self.context,
+            "token_metadata".to_string(),
+            "max_supply".to_string()
+        ).unwrap_or_default()
+    }
+
+    pub fn set_cap(&mut self, max_supply: Option<U256>, sender: Sender) {
+        self.call(
+            sender,
+            "set_cap",
+            runtime_args! {
+                "max_supply" => max_supply
+            }
+        )
+    }
+
     pub fn balance_of(&self, account: Key) -> U256 {
         //let key = format!("balances_{}", account);
         self.query_contract_dictionary(
@@ -167,8 +216,38 @@ impl Token {
     }
 
     pub fn allowance(&self, owner: Key, spender: Key) -> U256 {
-        let key = format!("allowances_{}_{}", owner, spender);
-        self.query_contract(&key).unwrap_or_default()
+        let mut bytes = owner.to_bytes().unwrap();
+        bytes.append(&mut spender.to_bytes().unwrap());
+        self.query_contract_dictionary(
+            self.ali,
+            &self.context,
+            "allowances".to_string(),
+            blake2b_hex(bytes)
+        ).unwrap_or_default()
+    }
+
+    /// how many named keys the stored contract holds.
+    pub fn named_key_count(&self) -> usize {
+        self.context
+            .query(self.ali, &[self.name.clone()])
+            .unwrap_or_else(|_| panic!("{} contract not found", self.name))
+            .into_contract()
+            .unwrap_or_else(|_| panic!("{} is not a contract", self.name))
+            .named_keys()
+            .len()
+    }
+
+    pub fn event_count(&self) -> u64 {
+        self.query_contract("event_count").unwrap_or_default()
+    }
+
+    pub fn event(&self, index: u64) -> BTreeMap<String, String> {
+        self.query_contract_dictionary(
+            self.ali,
+            &self.context,
+            "events".to_string(),
+            index.to_string()
+        ).unwrap()
     }
 
     pub fn transfer(&mut self, recipient: Key, amount: U256, sender: Sender) {
@@ -193,6 +272,28 @@ impl Token {
         );
     }
 
+    pub fn increase_allowance(&mut self, spender: Key, delta: U256, sender: Sender) {
+        self.call(
+            sender,
+            "increase_allowance",
+            runtime_args! {
+                "spender" => spender,
+                "delta" => delta
+            }
+        )
+    }
+
+    pub fn decrease_allowance(&mut self, spender: Key, delta: U256, sender: Sender) {
+        self.call(
+            sender,
+            "decrease_allowance",
+            runtime_args! {
+                "spender" => spender,
+                "delta" => delta
+            }
+        )
+    }
+
     pub fn transfer_from(
         &mut self,
         owner: Key,
@@ -227,6 +328,39 @@ impl Token {
         )
     }
 
+    pub fn set_minter_limits(&mut self, minter: Key, quota: Option<U256>, rate_limit: Option<U256>, sender: Sender) {
+        self.call(
+            sender,
+            "set_minter_limits",
+            runtime_args! {
+                "minter" => minter,
+                "quota" => quota,
+                "rate_limit" => rate_limit
+            }
+        )
+    }
+
+    /// what a minter can still mint right now, read through the `remaining_mint_limit` entry point.
+    pub fn remaining_mint_limit(&mut self, minter: Key) -> Option<U256> {
+        let session = SessionBuilder::new(
+            Code::from("mint_limit_session.wasm"),
+            runtime_args! {
+                "token" => Key::Hash(self.contract_hash()),
+                "minter" => minter
+            }
+        )
+            .with_address(self.ali)
+            .with_authorization_keys(&[self.ali])
+            .with_block_time(self.block_time)
+            .build();
+        self.context.run(session);
+        self.context
+            .query(self.ali, &["remaining_mint_limit".to_string()])
+            .unwrap_or_else(|_| panic!("remaining_mint_limit not found"))
+            .into_t()
+            .unwrap_or_else(|_| panic!("remaining_mint_limit has wrong type"))
+    }
+
     pub fn burn(
         &mut self,
         owner: Key,
@@ -242,4 +376,245 @@ impl Token {
             }
         )
     }
-}
\ No newline at end of file
+
+    pub fn burn_self(&mut self, amount: U256, sender: Sender) {
+        self.call(
+            sender,
+            "burn_self",
+            runtime_args! {
+                "amount" => amount
+            }
+        )
+    }
+
+    pub fn burn_from(&mut self, owner: Key, amount: U256, sender: Sender) {
+        self.call(
+            sender,
+            "burn_from",
+            runtime_args! {
+                "owner" => owner,
+                "amount" => amount
+            }
+        )
+    }
+
+    pub fn disable_forced_burn(&mut self, sender: Sender) {
+        self.call(sender, "disable_forced_burn", runtime_args! {})
+    }
+
+    pub fn swap_in(
+        &mut self,
+        tx_hash: &str,
+        account: Key,
+        amount: U256,
+        sender: Sender,
+    ) {
+        self.call(
+            sender,
+            "swap_in",
+            runtime_args! {
+                "tx_hash" => tx_hash.to_string(),
+                "account" => account,
+                "amount" => amount
+            }
+        )
+    }
+
+    pub fn swap_out(
+        &mut self,
+        amount: U256,
+        bind_address: &str,
+        to_chain_id: U256,
+        sender: Sender,
+    ) {
+        self.call(
+            sender,
+            "swap_out",
+            runtime_args! {
+                "amount" => amount,
+                "bind_address" => bind_address.to_string(),
+                "to_chain_id" => to_chain_id
+            }
+        )
+    }
+
+    pub fn governance(&self) -> Key {
+        self.query_contract_dictionary(
+            self.ali,
+            &self.context,
+            "external".to_string(),
+            "governance".to_string()
+        ).unwrap()
+    }
+
+    pub fn pending_governance(&self) -> Option<Key> {
+        self.query_contract_dictionary(
+            self.ali,
+            &self.context,
+            "external".to_string(),
+            "pending_governance".to_string()
+        ).unwrap_or_default()
+    }
+
+    pub fn propose_governance(&mut self, new_governance: Key, sender: Sender) {
+        self.call(
+            sender,
+            "propose_governance",
+            runtime_args! {
+                "new_governance" => new_governance
+            }
+        )
+    }
+
+    pub fn apply_governance(&mut self, sender: Sender) {
+        self.call(sender, "apply_governance", runtime_args! {})
+    }
+
+    pub fn is_frozen(&self, account: Key) -> bool {
+        self.query_contract_dictionary(
+            self.ali,
+            &self.context,
+            "frozen".to_string(),
+            key_to_str(&account)
+        ).unwrap_or_default()
+    }
+
+    pub fn freeze(&mut self, account: Key, sender: Sender) {
+        self.call(
+            sender,
+            "freeze",
+            runtime_args! {
+                "account" => account
+            }
+        )
+    }
+
+    pub fn unfreeze(&mut self, account: Key, sender: Sender) {
+        self.call(
+            sender,
+            "unfreeze",
+            runtime_args! {
+                "account" => account
+            }
+        )
+    }
+
+    pub fn wipe_frozen(&mut self, account: Key, sender: Sender) {
+        self.call(
+            sender,
+            "wipe_frozen",
+            runtime_args! {
+                "account" => account
+            }
+        )
+    }
+
+    pub fn paused(&self) -> bool {
+        self.query_contract_dictionary(
+            self.ali,
+            &self.context,
+            "external".to_string(),
+            "paused".to_string()
+        ).unwrap_or_default()
+    }
+
+    pub fn pause(&mut self, sender: Sender) {
+        self.call(sender, "pause", runtime_args! {})
+    }
+
+    pub fn unpause(&mut self, sender: Sender) {
+        self.call(sender, "unpause", runtime_args! {})
+    }
+
+    pub fn has_role(&self, role: &str, account: Key) -> bool {
+        self.query_contract_dictionary(
+            self.ali,
+            &self.context,
+            "roles".to_string(),
+            role_key(role, &account)
+        ).unwrap_or_default()
+    }
+
+    pub fn grant_role(&mut self, role: &str, account: Key, sender: Sender) {
+        self.call(
+            sender,
+            "grant_role",
+            runtime_args! {
+                "role" => role.to_string(),
+                "account" => account
+            }
+        )
+    }
+
+    pub fn revoke_role(&mut self, role: &str, account: Key, sender: Sender) {
+        self.call(
+            sender,
+            "revoke_role",
+            runtime_args! {
+                "role" => role.to_string(),
+                "account" => account
+            }
+        )
+    }
+
+    pub fn renounce_role(&mut self, role: &str, sender: Sender) {
+        self.call(
+            sender,
+            "renounce_role",
+            runtime_args! {
+                "role" => role.to_string()
+            }
+        )
+    }
+
+    /// stores a governance-proxy contract owned by the sender and returns its hash,
+    /// along with the package key the token sees it as.
+    pub fn deploy_governance_proxy(&mut self, sender: Sender) -> (Hash, Key) {
+        let Sender(address) = sender;
+        let session = SessionBuilder::new(Code::from("governance_proxy.wasm"), runtime_args! {})
+            .with_address(address)
+            .with_authorization_keys(&[address])
+            .with_block_time(self.block_time)
+            .build();
+        self.context.run(session);
+        let contract_hash = self.context
+            .query(address, &["GovernanceProxy_hash".to_string()])
+            .unwrap_or_else(|_| panic!("GovernanceProxy contract not found"))
+            .into_t()
+            .unwrap_or_else(|_| panic!("GovernanceProxy has wrong type"));
+        let package_hash: Hash = self.context
+            .query(address, &["GovernanceProxy_package_hash".to_string()])
+            .unwrap_or_else(|_| panic!("GovernanceProxy package not found"))
+            .into_t()
+            .unwrap_or_else(|_| panic!("GovernanceProxy package has wrong type"));
+        (contract_hash, Key::Hash(package_hash))
+    }
+
+    /// call a governance-proxy entry point acting on this token.
+    fn call_proxy(&mut self, proxy: Hash, sender: Sender, method: &str, mut args: RuntimeArgs) {
+        let Sender(address) = sender;
+        args.insert("token", Key::Hash(self.contract_hash())).unwrap();
+        let session = SessionBuilder::new(Code::Hash(proxy, method.to_string()), args)
+            .with_address(address)
+            .with_authorization_keys(&[address])
+            .with_block_time(self.block_time)
+            .build();
+        self.context.run(session);
+    }
+
+    pub fn proxy_mint(&mut self, proxy: Hash, owner: Key, amount: U256, sender: Sender) {
+        self.call_proxy(
+            proxy,
+            sender,
+            "mint",
+            runtime_args! {
+                "owner" => owner,
+                "amount" => amount
+            }
+        )
+    }
+
+    pub fn proxy_apply_governance(&mut self, proxy: Hash, sender: Sender) {
+        self.call_proxy(proxy, sender, "apply_governance", runtime_args! {})
+    }
+}
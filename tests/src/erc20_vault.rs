@@ -0,0 +1,232 @@
+use std::collections::BTreeMap;
+
+use casper_engine_test_support::{Code, Hash, SessionBuilder, TestContext, TestContextBuilder};
+use casper_types::{AsymmetricType, CLTyped, Key, PublicKey, RuntimeArgs, U256, U512, account::AccountHash, bytesrepr::FromBytes, runtime_args};
+
+use crate::erc20::key_to_str;
+
+// contains methods that can simulate a real-world deployment of a Casper-native token
+// and the vault bridging it out, sharing one chain, and transactions to invoke them.
+
+pub mod vault_cfg {
+    use super::*;
+    pub const TOKEN: &str = "NATIVE";
+    pub const DECIMALS: u8 = 8;
+    pub fn total_supply() -> U256 {
+        1_000.into()
+    }
+}
+
+pub struct Sender(pub AccountHash);
+
+pub struct Vault {
+    context: TestContext,
+    pub ali: AccountHash,
+    pub bob: AccountHash,
+    pub joe: AccountHash,
+}
+
+impl Vault {
+    /// deploys a `vault_cfg::TOKEN` token with the whole supply owned by ali and the vault,
+    /// with ali as governance of both.
+    pub fn deployed() -> Vault {
+        let ali = PublicKey::ed25519_from_bytes([3u8; 32]).unwrap();
+        let bob = PublicKey::ed25519_from_bytes([6u8; 32]).unwrap();
+        let joe = PublicKey::ed25519_from_bytes([9u8; 32]).unwrap();
+
+        let context = TestContextBuilder::new()
+            .with_public_key(ali.clone(), U512::from(500_000_000_000_000_000u64))
+            .with_public_key(bob.clone(), U512::from(500_000_000_000_000_000u64))
+            .build();
+        let mut vault = Vault {
+            context,
+            ali: ali.to_account_hash(),
+            bob: bob.to_account_hash(),
+            joe: joe.to_account_hash(),
+        };
+        vault.deploy("erc20.wasm", runtime_args! {
+            "token_name" => vault_cfg::TOKEN.to_string(),
+            "token_symbol" => vault_cfg::TOKEN.to_string(),
+            "token_decimals" => vault_cfg::DECIMALS,
+            "token_total_supply" => vault_cfg::total_supply(),
+            "token_max_supply" => None::<U256>,
+            "governance" => Key::Account(vault.ali)
+        });
+        vault.deploy("erc20_vault.wasm", runtime_args! {
+            "governance" => Key::Account(vault.ali)
+        });
+        vault
+    }
+
+    fn deploy(&mut self, wasm: &str, args: RuntimeArgs) {
+        let session = SessionBuilder::new(Code::from(wasm), args)
+            .with_address(self.ali)
+            .with_authorization_keys(&[self.ali])
+            .build();
+        self.context.run(session);
+    }
+
+    /// a contract hash saved in ali's named keys under `{name}_hash`.
+    pub fn hash(&self, name: &str) -> Hash {
+        self.context
+            .query(self.ali, &[format!("{}_hash", name)])
+            .unwrap_or_else(|_| panic!("{} contract not found", name))
+            .into_t()
+            .unwrap_or_else(|_| panic!("{} has wrong type", name))
+    }
+
+    pub fn token(&self) -> Key {
+        Key::Hash(self.hash(vault_cfg::TOKEN))
+    }
+
+    /// the vault's package, which is how the token sees it.
+    pub fn vault(&self) -> Key {
+        Key::Hash(self.hash("ERC20_Vault_package"))
+    }
+
+    /// query a contract's dictionary's key.
+    fn query_contract_dictionary<T: CLTyped + FromBytes>(
+        &self,
+        key: Key,
+        dictionary_name: &str,
+        name: String,
+    ) -> Option<T> {
+        match self.context.query_dictionary_item(key, Some(dictionary_name.to_string()), name.clone()) {
+            Err(_) => None,
+            Ok(maybe_value) => {
+                let value = maybe_value
+                    .into_t()
+                    .unwrap_or_else(|_| panic!("{} is not the expected type.", name));
+                Some(value)
+            }
+        }
+    }
+
+    /// call a contract's specific entry point.
+    fn call(&mut self, sender: Sender, contract_hash: Hash, method: &str, args: RuntimeArgs) {
+        let Sender(address) = sender;
+        let code = Code::Hash(contract_hash, method.to_string());
+        let session = SessionBuilder::new(code, args)
+            .with_address(address)
+            .with_authorization_keys(&[address])
+            .build();
+        self.context.run(session);
+    }
+
+    pub fn token_balance(&self, account: Key) -> U256 {
+        self.query_contract_dictionary(
+            self.token(),
+            "balances",
+            key_to_str(&account)
+        ).unwrap_or_default()
+    }
+
+    /// what the vault still holds of `token`, as the `locked` entry point computes it.
+    pub fn locked(&self, token: Key) -> U256 {
+        let deposited: U256 = self.query_contract_dictionary(Key::Hash(self.hash("ERC20_Vault")), "deposited", key_to_str(&token))
+            .unwrap_or_default();
+        let withdrawn: U256 = self.query_contract_dictionary(Key::Hash(self.hash("ERC20_Vault")), "withdrawn", key_to_str(&token))
+            .unwrap_or_default();
+        deposited - withdrawn
+    }
+
+    pub fn deposit_count(&self) -> u64 {
+        self.query_contract_dictionary(
+            Key::Hash(self.hash("ERC20_Vault")),
+            "erc20_vault_data",
+            "deposit_count".to_string()
+        ).unwrap_or_default()
+    }
+
+    pub fn deposit_record(&self, nonce: u64) -> BTreeMap<String, String> {
+        self.query_contract_dictionary(
+            Key::Hash(self.hash("ERC20_Vault")),
+            "deposits",
+            nonce.to_string()
+        ).unwrap()
+    }
+
+    pub fn event(&self, index: u64) -> BTreeMap<String, String> {
+        self.query_contract_dictionary(
+            Key::Hash(self.hash("ERC20_Vault")),
+            "erc20_vault_events",
+            index.to_string()
+        ).unwrap()
+    }
+
+    pub fn approve(&mut self, spender: Key, amount: U256, sender: Sender) {
+        self.call(
+            sender,
+            self.hash(vault_cfg::TOKEN),
+            "approve",
+            runtime_args! {
+                "spender" => spender,
+                "amount" => amount
+            }
+        )
+    }
+
+    pub fn allow_token(&mut self, token: Key, sender: Sender) {
+        self.call(
+            sender,
+            self.hash("ERC20_Vault"),
+            "allow_token",
+            runtime_args! {
+                "token" => token
+            }
+        )
+    }
+
+    pub fn disallow_token(&mut self, token: Key, sender: Sender) {
+        self.call(
+            sender,
+            self.hash("ERC20_Vault"),
+            "disallow_token",
+            runtime_args! {
+                "token" => token
+            }
+        )
+    }
+
+    pub fn deposit(
+        &mut self,
+        token: Key,
+        amount: U256,
+        to_chain_id: U256,
+        to_address: &str,
+        sender: Sender,
+    ) {
+        self.call(
+            sender,
+            self.hash("ERC20_Vault"),
+            "deposit",
+            runtime_args! {
+                "token" => token,
+                "amount" => amount,
+                "to_chain_id" => to_chain_id,
+                "to_address" => to_address.to_string()
+            }
+        )
+    }
+
+    pub fn withdraw(
+        &mut self,
+        tx_id: &str,
+        token: Key,
+        recipient: Key,
+        amount: U256,
+        sender: Sender,
+    ) {
+        self.call(
+            sender,
+            self.hash("ERC20_Vault"),
+            "withdraw",
+            runtime_args! {
+                "tx_id" => tx_id.to_string(),
+                "token" => token,
+                "recipient" => recipient,
+                "amount" => amount
+            }
+        )
+    }
+}
@@ -1,5 +1,7 @@
 use casper_engine_test_support::{Code, Hash, SessionBuilder, TestContext, TestContextBuilder};
-use casper_types::{AsymmetricType, CLTyped, ContractHash, PublicKey, RuntimeArgs, U256, U512, account::AccountHash, bytesrepr::FromBytes, runtime_args};
+use casper_types::{AsymmetricType, CLTyped, ContractHash, Key, PublicKey, RuntimeArgs, U256, U512, account::AccountHash, bytesrepr::{FromBytes, ToBytes}, runtime_args};
+
+use crate::erc20::{key_to_str, role_key};
 
 // contains methods that can simulate a real-world deployment (storing the contract in the blockchain)
 // and transactions to invoke the methods in the contract.
@@ -8,6 +10,7 @@ pub struct Sender(pub AccountHash);
 
 pub struct Factory {
     context: TestContext,
+    block_time: u64,
     pub ali: AccountHash,
     pub bob: AccountHash,
     pub joe: AccountHash,
@@ -25,7 +28,7 @@ impl Factory {
             .build();
         let session_code = Code::from("factory.wasm");
         let session_args = runtime_args! {
-            "governance" => ali.to_account_hash()
+            "governance" => Key::Account(ali.to_account_hash())
         };
         let session = SessionBuilder::new(session_code, session_args)
             .with_address(ali.to_account_hash())
@@ -34,6 +37,7 @@ impl Factory {
         context.run(session);
         Factory {
             context,
+            block_time: 0,
             ali: ali.to_account_hash(),
             bob: bob.to_account_hash(),
             joe: joe.to_account_hash(),
@@ -74,6 +78,51 @@ impl Factory {
         let session = SessionBuilder::new(code, args)
             .with_address(address)
             .with_authorization_keys(&[address])
+            .with_block_time(self.block_time)
+            .build();
+        self.context.run(session);
+    }
+
+    /// moves the block time used by the following calls forward.
+    pub fn advance_block_time(&mut self, millis: u64) {
+        self.block_time += millis;
+    }
+
+    /// stores a governance-proxy contract owned by the sender and returns its hash,
+    /// along with the package key the factory sees it as.
+    pub fn deploy_governance_proxy(&mut self, sender: Sender) -> (Hash, Key) {
+        let Sender(address) = sender;
+        let session = SessionBuilder::new(Code::from("governance_proxy.wasm"), runtime_args! {})
+            .with_address(address)
+            .with_authorization_keys(&[address])
+            .with_block_time(self.block_time)
+            .build();
+        self.context.run(session);
+        let contract_hash = self.context
+            .query(address, &["GovernanceProxy_hash".to_string()])
+            .unwrap_or_else(|_| panic!("GovernanceProxy contract not found"))
+            .into_t()
+            .unwrap_or_else(|_| panic!("GovernanceProxy has wrong type"));
+        let package_hash: Hash = self.context
+            .query(address, &["GovernanceProxy_package_hash".to_string()])
+            .unwrap_or_else(|_| panic!("GovernanceProxy package not found"))
+            .into_t()
+            .unwrap_or_else(|_| panic!("GovernanceProxy package has wrong type"));
+        (contract_hash, Key::Hash(package_hash))
+    }
+
+    /// makes a governance proxy call one of the factory's entry points.
+    pub fn proxy_execute(&mut self, proxy: Hash, method: &str, args: RuntimeArgs, sender: Sender) {
+        let Sender(address) = sender;
+        let proxy_args = runtime_args! {
+            "contract" => Key::Hash(self.contract_hash()),
+            "entry_point" => method.to_string(),
+            "args" => args.to_bytes().unwrap()
+        };
+        let session = SessionBuilder::new(Code::Hash(proxy, "execute".to_string()), proxy_args)
+            .with_address(address)
+            .with_authorization_keys(&[address])
+            .with_block_time(self.block_time)
             .build();
         self.context.run(session);
     }
@@ -93,7 +142,7 @@ impl Factory {
         token_symbol: String,
         token_decimals: u8,
         token_total_supply: U256,
-        governance: AccountHash,
+        governance: Key,
         sender: Sender,
     ) {
         self.call(
@@ -104,8 +153,88 @@ impl Factory {
                 "token_symbol" => token_symbol,
                 "token_decimals" => token_decimals,
                 "token_total_supply" => token_total_supply,
+                "token_max_supply" => None::<U256>,
                 "governance" => governance
             }
         )
     }
-}
\ No newline at end of file
+
+    pub fn factory_governance(&self) -> Key {
+        self.query_contract_dictionary(
+            self.ali,
+            &self.context,
+            "factory_external".to_string(),
+            "governance".to_string()
+        ).unwrap()
+    }
+
+    pub fn propose_factory_governance(&mut self, new_governance: Key, sender: Sender) {
+        self.call(
+            sender,
+            "propose_factory_governance",
+            runtime_args! {
+                "new_governance" => new_governance
+            }
+        )
+    }
+
+    pub fn apply_factory_governance(&mut self, sender: Sender) {
+        self.call(sender, "apply_factory_governance", runtime_args! {})
+    }
+
+    fn token_hash(&self, token_name: &str) -> Hash {
+        self.get_erc20_hash(token_name.to_string()).value()
+    }
+
+    /// call an entry point of a token created by the factory.
+    fn call_token(&mut self, token_name: &str, sender: Sender, method: &str, args: RuntimeArgs) {
+        let Sender(address) = sender;
+        let code = Code::Hash(self.token_hash(token_name), method.to_string());
+        let session = SessionBuilder::new(code, args)
+            .with_address(address)
+            .with_authorization_keys(&[address])
+            .with_block_time(self.block_time)
+            .build();
+        self.context.run(session);
+    }
+
+    pub fn token_balance_of(&self, token_name: &str, account: Key) -> U256 {
+        self.context
+            .query_dictionary_item(Key::Hash(self.token_hash(token_name)), Some("balances".to_string()), key_to_str(&account))
+            .ok()
+            .map(|value| value.into_t().unwrap())
+            .unwrap_or_default()
+    }
+
+    pub fn token_has_role(&self, token_name: &str, role: &str, account: Key) -> bool {
+        self.context
+            .query_dictionary_item(Key::Hash(self.token_hash(token_name)), Some("roles".to_string()), role_key(role, &account))
+            .ok()
+            .map(|value| value.into_t().unwrap())
+            .unwrap_or_default()
+    }
+
+    pub fn token_grant_role(&mut self, token_name: &str, role: &str, account: Key, sender: Sender) {
+        self.call_token(
+            token_name,
+            sender,
+            "grant_role",
+            runtime_args! {
+                "role" => role.to_string(),
+                "account" => account
+            }
+        )
+    }
+
+    pub fn token_mint(&mut self, token_name: &str, owner: Key, amount: U256, sender: Sender) {
+        self.call_token(
+            token_name,
+            sender,
+            "mint",
+            runtime_args! {
+                "owner" => owner,
+                "amount" => amount
+            }
+        )
+    }
+}
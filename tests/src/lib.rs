@@ -8,4 +8,10 @@ pub mod erc20;
 pub mod cspr_holder;
 
 #[cfg(test)]
-pub mod factory;
\ No newline at end of file
+pub mod factory;
+
+#[cfg(test)]
+pub mod router;
+
+#[cfg(test)]
+pub mod erc20_vault;
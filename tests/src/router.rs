@@ -0,0 +1,550 @@
+use std::collections::BTreeMap;
+
+use casper_engine_test_support::{Code, Hash, SessionBuilder, TestContext, TestContextBuilder};
+use casper_types::{AsymmetricType, CLTyped, ContractHash, Key, PublicKey, RuntimeArgs, U256, U512, URef, account::AccountHash, bytesrepr::FromBytes, runtime_args};
+
+// contains methods that can simulate a real-world deployment of the whole bridge
+// (factory, factory token, cspr holder and router sharing one chain) and transactions to invoke them.
+
+pub mod bridge_cfg {
+    use super::*;
+    pub const TOKEN: &str = "WETH";
+    pub const DECIMALS: u8 = 18;
+    pub fn total_supply() -> U256 {
+        1_000.into()
+    }
+}
+
+pub const WCSPR: &str = "WCSPR";
+
+pub struct Sender(pub AccountHash);
+
+pub struct Router {
+    context: TestContext,
+    pub ali: AccountHash,
+    pub bob: AccountHash,
+    pub joe: AccountHash,
+    block_time: u64,
+}
+
+fn key_to_str(key: &Key) -> String {
+    match key {
+        Key::Account(account) => account.to_string(),
+        Key::Hash(package) => hex::encode(package),
+        _ => "UnexpectedKeyVariant".to_string()
+    }
+}
+
+impl Router {
+    /// deploys the factory with a `bridge_cfg::TOKEN` token, the cspr holder and the router,
+    /// with ali as governance everywhere, the router a minter of the token and trusted by the holder.
+    pub fn deployed() -> Router {
+        let ali = PublicKey::ed25519_from_bytes([3u8; 32]).unwrap();
+        let bob = PublicKey::ed25519_from_bytes([6u8; 32]).unwrap();
+        let joe = PublicKey::ed25519_from_bytes([9u8; 32]).unwrap();
+
+        let context = TestContextBuilder::new()
+            .with_public_key(ali.clone(), U512::from(500_000_000_000_000_000u64))
+            .with_public_key(bob.clone(), U512::from(500_000_000_000_000_000u64))
+            .build();
+        let mut router = Router {
+            context,
+            ali: ali.to_account_hash(),
+            bob: bob.to_account_hash(),
+            joe: joe.to_account_hash(),
+            block_time: 0,
+        };
+        router.deploy("factory.wasm", runtime_args! {
+            "governance" => Key::Account(router.ali)
+        });
+        router.call(
+            Sender(router.ali),
+            router.hash("Factory"),
+            "create_erc20",
+            runtime_args! {
+                "token_name" => bridge_cfg::TOKEN.to_string(),
+                "token_symbol" => bridge_cfg::TOKEN.to_string(),
+                "token_decimals" => bridge_cfg::DECIMALS,
+                "token_total_supply" => bridge_cfg::total_supply(),
+                "token_max_supply" => None::<U256>,
+                "governance" => Key::Account(router.ali)
+            }
+        );
+        router.deploy("cspr_holder.wasm", runtime_args! {
+            "governance" => Key::Account(router.ali)
+        });
+        router.deploy("router.wasm", runtime_args! {
+            "governance" => Key::Account(router.ali),
+            "factory_hash" => Key::Hash(router.hash("Factory")),
+            "cspr_holder_hash" => Key::Hash(router.hash("CSPR_Holder"))
+        });
+        // Contracts are known to each other by their package.
+        let router_key = Key::Hash(router.hash("Router_package"));
+        router.grant_role("minter", router_key, Sender(router.ali));
+        router.call(
+            Sender(router.ali),
+            router.hash("CSPR_Holder"),
+            "set_router",
+            runtime_args! {
+                "router" => Some(router_key)
+            }
+        );
+        router.call(
+            Sender(router.ali),
+            router.hash("CSPR_Holder"),
+            "add_signer",
+            runtime_args! {
+                "signer" => router_key
+            }
+        );
+        router
+    }
+
+    fn deploy(&mut self, wasm: &str, args: RuntimeArgs) {
+        let session = SessionBuilder::new(Code::from(wasm), args)
+            .with_address(self.ali)
+            .with_authorization_keys(&[self.ali])
+            .build();
+        self.context.run(session);
+    }
+
+    /// a contract hash saved in ali's named keys under `{name}_hash`.
+    pub fn hash(&self, name: &str) -> Hash {
+        self.context
+            .query(self.ali, &[format!("{}_hash", name)])
+            .unwrap_or_else(|_| panic!("{} contract not found", name))
+            .into_t()
+            .unwrap_or_else(|_| panic!("{} has wrong type", name))
+    }
+
+    pub fn token_hash(&self) -> Hash {
+        let token_hash: ContractHash = self.query_contract_dictionary(
+            Key::Account(self.ali),
+            "tokens",
+            bridge_cfg::TOKEN.to_string()
+        ).unwrap();
+        token_hash.value()
+    }
+
+    /// query a contract's dictionary's key.
+    fn query_contract_dictionary<T: CLTyped + FromBytes>(
+        &self,
+        key: Key,
+        dictionary_name: &str,
+        name: String,
+    ) -> Option<T> {
+        match self.context.query_dictionary_item(key, Some(dictionary_name.to_string()), name.clone()) {
+            Err(_) => None,
+            Ok(maybe_value) => {
+                let value = maybe_value
+                    .into_t()
+                    .unwrap_or_else(|_| panic!("{} is not the expected type.", name));
+                Some(value)
+            }
+        }
+    }
+
+    /// call a contract's specific entry point.
+    fn call(&mut self, sender: Sender, contract_hash: Hash, method: &str, args: RuntimeArgs) {
+        let Sender(address) = sender;
+        let code = Code::Hash(contract_hash, method.to_string());
+        let session = SessionBuilder::new(code, args)
+            .with_address(address)
+            .with_authorization_keys(&[address])
+            .with_block_time(self.block_time)
+            .build();
+        self.context.run(session);
+    }
+
+    /// moves the block time used by the following calls forward.
+    pub fn advance_block_time(&mut self, millis: u64) {
+        self.block_time += millis;
+    }
+
+    pub fn token_balance(&self, account: Key) -> U256 {
+        self.query_contract_dictionary(
+            Key::Hash(self.token_hash()),
+            "balances",
+            key_to_str(&account)
+        ).unwrap_or_default()
+    }
+
+    pub fn token_total_supply(&self) -> U256 {
+        self.query_contract_dictionary(
+            Key::Hash(self.token_hash()),
+            "token_metadata",
+            "total_supply".to_string()
+        ).unwrap()
+    }
+
+    pub fn total_locked(&self) -> U512 {
+        self.query_contract_dictionary(
+            Key::Hash(self.hash("CSPR_Holder")),
+            "cspr_holder_data",
+            "total_locked".to_string()
+        ).unwrap_or_default()
+    }
+
+    /// the CSPR an account has locked into the holder, directly or through the router.
+    pub fn deposits_of(&self, account: Key) -> U512 {
+        self.query_contract_dictionary(
+            Key::Hash(self.hash("CSPR_Holder")),
+            "deposits_by_account",
+            key_to_str(&account)
+        ).unwrap_or_default()
+    }
+
+    pub fn deposit_record(&self, deposit_id: u64) -> BTreeMap<String, String> {
+        self.query_contract_dictionary(
+            Key::Hash(self.hash("CSPR_Holder")),
+            "deposits",
+            deposit_id.to_string()
+        ).unwrap()
+    }
+
+    pub fn event_count(&self) -> u64 {
+        self.context
+            .query(self.ali, &["Router".to_string(), "router_event_count".to_string()])
+            .ok()
+            .map(|value| value.into_t().unwrap())
+            .unwrap_or_default()
+    }
+
+    pub fn event(&self, index: u64) -> BTreeMap<String, String> {
+        self.query_contract_dictionary(
+            Key::Hash(self.hash("Router")),
+            "router_events",
+            index.to_string()
+        ).unwrap()
+    }
+
+    pub fn main_purse(&self, account: AccountHash) -> URef {
+        self.context
+            .main_purse_address(account)
+            .unwrap_or_else(|| panic!("{} has no main purse", account))
+    }
+
+    pub fn purse_balance(&self, purse: URef) -> U512 {
+        self.context.get_balance(purse.addr())
+    }
+
+    /// store a `purse-receiver` contract as ali, returning its package key and a read/add copy of its purse.
+    pub fn deploy_purse_receiver(&mut self) -> (Key, URef) {
+        self.deploy("purse_receiver.wasm", runtime_args! {});
+        let purse = self.context
+            .query(self.ali, &["PurseReceiver_purse".to_string()])
+            .unwrap_or_else(|_| panic!("PurseReceiver_purse not found"))
+            .into_t()
+            .unwrap_or_else(|_| panic!("PurseReceiver_purse has wrong type"));
+        (Key::Hash(self.hash("PurseReceiver_package")), purse)
+    }
+
+    /// an account's main purse balance, zero if the account doesn't exist yet.
+    pub fn balance_of(&self, account: AccountHash) -> U512 {
+        self.context
+            .main_purse_address(account)
+            .map(|purse| self.context.get_balance(purse.addr()))
+            .unwrap_or_default()
+    }
+
+    /// creates a `WCSPR` factory token with mote decimals, lets the holder mint it
+    /// and switches the holder to wrapped mode.
+    pub fn enable_wcspr(&mut self) {
+        self.call(
+            Sender(self.ali),
+            self.hash("Factory"),
+            "create_erc20",
+            runtime_args! {
+                "token_name" => WCSPR.to_string(),
+                "token_symbol" => WCSPR.to_string(),
+                "token_decimals" => 9u8,
+                "token_total_supply" => U256::zero(),
+                "token_max_supply" => None::<U256>,
+                "governance" => Key::Account(self.ali)
+            }
+        );
+        self.call(
+            Sender(self.ali),
+            self.wcspr_hash(),
+            "grant_role",
+            runtime_args! {
+                "role" => "minter".to_string(),
+                "account" => Key::Hash(self.hash("CSPR_Holder_package"))
+            }
+        );
+        self.set_wcspr(Key::Hash(self.wcspr_hash()), Sender(self.ali));
+    }
+
+    pub fn grant_role(&mut self, role: &str, account: Key, sender: Sender) {
+        self.call(
+            sender,
+            self.token_hash(),
+            "grant_role",
+            runtime_args! {
+                "role" => role.to_string(),
+                "account" => account
+            }
+        )
+    }
+
+    pub fn revoke_role(&mut self, role: &str, account: Key, sender: Sender) {
+        self.call(
+            sender,
+            self.token_hash(),
+            "revoke_role",
+            runtime_args! {
+                "role" => role.to_string(),
+                "account" => account
+            }
+        )
+    }
+
+    /// lets the holder burn `amount` of the sender's WCSPR on redeem.
+    pub fn approve_holder(&mut self, amount: U256, sender: Sender) {
+        self.call(
+            sender,
+            self.wcspr_hash(),
+            "approve",
+            runtime_args! {
+                "spender" => Key::Hash(self.hash("CSPR_Holder_package")),
+                "amount" => amount
+            }
+        )
+    }
+
+    pub fn disable_wcspr_forced_burn(&mut self, sender: Sender) {
+        self.call(sender, self.wcspr_hash(), "disable_forced_burn", runtime_args! {})
+    }
+
+    pub fn set_wcspr(&mut self, token: Key, sender: Sender) {
+        self.call(
+            sender,
+            self.hash("CSPR_Holder"),
+            "set_wcspr",
+            runtime_args! {
+                "token" => token
+            }
+        )
+    }
+
+    pub fn wcspr_hash(&self) -> Hash {
+        let token_hash: ContractHash = self.query_contract_dictionary(
+            Key::Account(self.ali),
+            "tokens",
+            WCSPR.to_string()
+        ).unwrap();
+        token_hash.value()
+    }
+
+    pub fn wcspr_balance(&self, account: Key) -> U256 {
+        self.query_contract_dictionary(
+            Key::Hash(self.wcspr_hash()),
+            "balances",
+            key_to_str(&account)
+        ).unwrap_or_default()
+    }
+
+    pub fn wcspr_total_supply(&self) -> U256 {
+        self.query_contract_dictionary(
+            Key::Hash(self.wcspr_hash()),
+            "token_metadata",
+            "total_supply".to_string()
+        ).unwrap()
+    }
+
+    /// the holder's purse balance, read through the copy saved in ali's named keys.
+    pub fn holder_purse_balance(&self) -> U512 {
+        let purse: URef = self.context
+            .query(self.ali, &["CSPR_Holder_purse".to_string()])
+            .unwrap_or_else(|_| panic!("CSPR_Holder_purse not found"))
+            .into_t()
+            .unwrap_or_else(|_| panic!("CSPR_Holder_purse has wrong type"));
+        self.context.get_balance(purse.addr())
+    }
+
+    /// lock CSPR from the sender's main purse straight into the holder.
+    pub fn lock(&mut self, amount: U512, sender: Sender) {
+        let Sender(address) = sender;
+        let src_purse = self.main_purse(address);
+        self.call(
+            Sender(address),
+            self.hash("CSPR_Holder"),
+            "lock",
+            runtime_args! {
+                "src_purse" => src_purse,
+                "amount" => amount,
+                "to_chain_id" => U256::from(56),
+                "to_address" => "0x7Be8076f4EA4A4AD08075C2508e481d6C946D12b".to_string()
+            }
+        )
+    }
+
+    /// wrap CSPR from the sender's main purse into WCSPR.
+    pub fn wrap(&mut self, amount: U512, sender: Sender) {
+        let Sender(address) = sender;
+        let src_purse = self.main_purse(address);
+        self.call(
+            Sender(address),
+            self.hash("CSPR_Holder"),
+            "wrap",
+            runtime_args! {
+                "src_purse" => src_purse,
+                "amount" => amount
+            }
+        )
+    }
+
+    /// lock CSPR on someone else's behalf, only the router is allowed to.
+    pub fn lock_for(&mut self, depositor: Key, amount: U512, sender: Sender) {
+        let Sender(address) = sender;
+        let src_purse = self.main_purse(address);
+        self.call(
+            Sender(address),
+            self.hash("CSPR_Holder"),
+            "lock_for",
+            runtime_args! {
+                "src_purse" => src_purse,
+                "amount" => amount,
+                "to_chain_id" => U256::from(56),
+                "to_address" => "0x7Be8076f4EA4A4AD08075C2508e481d6C946D12b".to_string(),
+                "depositor" => depositor
+            }
+        )
+    }
+
+    pub fn redeem(&mut self, amount: U512, sender: Sender) {
+        self.call(
+            sender,
+            self.hash("CSPR_Holder"),
+            "redeem",
+            runtime_args! {
+                "amount" => amount
+            }
+        )
+    }
+
+    pub fn set_refund_timeout(&mut self, refund_timeout: Option<u64>, sender: Sender) {
+        self.call(
+            sender,
+            self.hash("CSPR_Holder"),
+            "set_refund_timeout",
+            runtime_args! {
+                "refund_timeout" => refund_timeout
+            }
+        )
+    }
+
+    pub fn refund(&mut self, deposit_id: u64, sender: Sender) {
+        self.call(
+            sender,
+            self.hash("CSPR_Holder"),
+            "refund",
+            runtime_args! {
+                "deposit_id" => deposit_id
+            }
+        )
+    }
+
+    pub fn reconcile(&mut self, sender: Sender) {
+        self.call(sender, self.hash("CSPR_Holder"), "reconcile", runtime_args! {})
+    }
+
+    /// lets the router burn `amount` of the sender's bridged tokens on swap-out.
+    pub fn approve_router(&mut self, amount: U256, sender: Sender) {
+        self.call(
+            sender,
+            self.token_hash(),
+            "approve",
+            runtime_args! {
+                "spender" => Key::Hash(self.hash("Router_package")),
+                "amount" => amount
+            }
+        )
+    }
+
+    pub fn any_swap_out(
+        &mut self,
+        token: &str,
+        to: &str,
+        amount: U256,
+        to_chain_id: U256,
+        sender: Sender,
+    ) {
+        self.call(
+            sender,
+            self.hash("Router"),
+            "any_swap_out",
+            runtime_args! {
+                "token" => token.to_string(),
+                "to" => to.to_string(),
+                "amount" => amount,
+                "to_chain_id" => to_chain_id
+            }
+        )
+    }
+
+    pub fn any_swap_in(
+        &mut self,
+        tx_hash: &str,
+        token: &str,
+        to: Key,
+        amount: U256,
+        from_chain_id: U256,
+        sender: Sender,
+    ) {
+        self.call(
+            sender,
+            self.hash("Router"),
+            "any_swap_in",
+            runtime_args! {
+                "tx_hash" => tx_hash.to_string(),
+                "token" => token.to_string(),
+                "to" => to,
+                "amount" => amount,
+                "from_chain_id" => from_chain_id
+            }
+        )
+    }
+
+    pub fn any_swap_out_native(
+        &mut self,
+        src_purse: URef,
+        to: &str,
+        amount: U512,
+        to_chain_id: U256,
+        sender: Sender,
+    ) {
+        self.call(
+            sender,
+            self.hash("Router"),
+            "any_swap_out_native",
+            runtime_args! {
+                "src_purse" => src_purse,
+                "to" => to.to_string(),
+                "amount" => amount,
+                "to_chain_id" => to_chain_id
+            }
+        )
+    }
+
+    pub fn any_swap_in_native(
+        &mut self,
+        tx_hash: &str,
+        target: Key,
+        amount: U512,
+        from_chain_id: U256,
+        sender: Sender,
+    ) {
+        self.call(
+            sender,
+            self.hash("Router"),
+            "any_swap_in_native",
+            runtime_args! {
+                "tx_hash" => tx_hash.to_string(),
+                "target" => target,
+                "amount" => amount,
+                "from_chain_id" => from_chain_id
+            }
+        )
+    }
+}
@@ -1,10 +1,14 @@
 
 use casper_engine_test_support::AccountHash;
-use casper_types::{AccessRights, AsymmetricType, ContractHash, Key, PublicKey, U256, U512, URef};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use casper_types::{AccessRights, ApiError, AsymmetricType, ContractHash, Key, PublicKey, U256, U512, runtime_args};
 
 use crate::erc20::{token_cfg, Sender, Token};
 use crate::cspr_holder::{Sender as CSPR_Sender, CsprHolder};
 use crate::factory::{Sender as F_Sender, Factory};
+use crate::router::{bridge_cfg, Sender as R_Sender, Router};
+use crate::erc20_vault::{Sender as V_Sender, Vault};
 
 // ------------ START - ERC20 Tests ------------
 
@@ -12,6 +16,18 @@ fn to_key(account: AccountHash) -> Key {
     Key::Account(account)
 }
 
+/// runs a failing deploy and checks it reverted with the given `ApiError::User` code.
+fn assert_user_error<F: FnOnce()>(code: u16, deploy: F) {
+    let payload = catch_unwind(AssertUnwindSafe(deploy)).expect_err("the deploy should have failed");
+    let message = payload
+        .downcast_ref::<String>()
+        .cloned()
+        .or_else(|| payload.downcast_ref::<&str>().map(|message| message.to_string()))
+        .unwrap_or_default();
+    let expected = format!("{:?}", ApiError::User(code));
+    assert!(message.contains(&expected), "expected {} but got: {}", expected, message);
+}
+
 #[test]
 fn test_erc20_deploy() {
     let t = Token::deployed("ERC20", "ERC");
@@ -37,11 +53,20 @@ fn test_erc20_transfer() {
 }
 
 #[test]
-#[should_panic]
 fn test_erc20_transfer_too_much() {
     let amount = 1.into();
     let mut t = Token::deployed("ERC20", "ERC");
-    t.transfer(to_key(t.ali), amount, Sender(t.bob));
+    let (ali, bob) = (t.ali, t.bob);
+    // Error::InsufficientBalance
+    assert_user_error(8, || t.transfer(to_key(ali), amount, Sender(bob)));
+}
+
+#[test]
+fn test_erc20_transfer_to_zero_key() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let ali = t.ali;
+    // Error::TransferToZeroKey
+    assert_user_error(10, || t.transfer(Key::Hash([0u8; 32]), 1.into(), Sender(ali)));
 }
 
 #[test]
@@ -55,6 +80,39 @@ fn test_erc20_approve() {
     assert_eq!(t.allowance(to_key(t.bob), to_key(t.ali)), 0.into());
 }
 
+#[test]
+fn test_erc20_increase_decrease_allowance() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    t.increase_allowance(to_key(t.bob), 10.into(), Sender(t.ali));
+    t.increase_allowance(to_key(t.bob), 5.into(), Sender(t.ali));
+    assert_eq!(t.allowance(to_key(t.ali), to_key(t.bob)), 15.into());
+    t.decrease_allowance(to_key(t.bob), 12.into(), Sender(t.ali));
+    assert_eq!(t.allowance(to_key(t.ali), to_key(t.bob)), 3.into());
+    let approval = t.event(t.event_count() - 1);
+    assert_eq!(approval.get("event_type").unwrap(), "approval");
+    assert_eq!(approval.get("allowance").unwrap(), "3");
+}
+
+#[test]
+fn test_erc20_increase_allowance_overflow() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let (ali, bob) = (t.ali, t.bob);
+    t.approve(to_key(bob), U256::MAX, Sender(ali));
+    // Error::AllowanceOverflow
+    assert_user_error(18, || t.increase_allowance(to_key(bob), 1.into(), Sender(ali)));
+    assert_eq!(t.allowance(to_key(ali), to_key(bob)), U256::MAX);
+}
+
+#[test]
+fn test_erc20_decrease_allowance_below_zero() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let (ali, bob) = (t.ali, t.bob);
+    t.approve(to_key(bob), 3.into(), Sender(ali));
+    // Error::InsufficientAllowance
+    assert_user_error(9, || t.decrease_allowance(to_key(bob), 4.into(), Sender(ali)));
+    assert_eq!(t.allowance(to_key(ali), to_key(bob)), 3.into());
+}
+
 #[test]
 fn test_erc20_transfer_from() {
     let allowance = 10.into();
@@ -70,11 +128,45 @@ fn test_erc20_transfer_from() {
 }
 
 #[test]
-#[should_panic]
 fn test_erc20_transfer_from_too_much() {
     let amount = token_cfg::total_supply().checked_add(1.into()).unwrap();
     let mut t = Token::deployed("ERC20", "ERC");
-    t.transfer_from(to_key(t.ali), to_key(t.joe), amount, Sender(t.bob));
+    let (ali, bob, joe) = (t.ali, t.bob, t.joe);
+    // Error::InsufficientAllowance
+    assert_user_error(9, || t.transfer_from(to_key(ali), to_key(joe), amount, Sender(bob)));
+}
+
+#[test]
+fn test_erc20_transfer_from_over_allowance() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let (ali, bob, joe) = (t.ali, t.bob, t.joe);
+    t.approve(to_key(bob), 5.into(), Sender(ali));
+    // Error::InsufficientAllowance, raised before any balance moves.
+    assert_user_error(9, || t.transfer_from(to_key(ali), to_key(joe), 6.into(), Sender(bob)));
+    assert_eq!(t.balance_of(to_key(ali)), token_cfg::total_supply());
+    assert_eq!(t.balance_of(to_key(joe)), 0.into());
+    assert_eq!(t.allowance(to_key(ali), to_key(bob)), 5.into());
+}
+
+#[test]
+fn test_erc20_transfer_from_over_balance() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let (ali, bob, joe) = (t.ali, t.bob, t.joe);
+    t.approve(to_key(ali), 5.into(), Sender(bob));
+    // Error::InsufficientBalance
+    assert_user_error(8, || t.transfer_from(to_key(bob), to_key(joe), 5.into(), Sender(ali)));
+}
+
+#[test]
+fn test_erc20_approvals_add_no_named_keys() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let named_keys = t.named_key_count();
+    for seed in 1..=10u8 {
+        let spender = Key::Account(AccountHash::new([seed; 32]));
+        t.approve(spender, seed.into(), Sender(t.ali));
+        assert_eq!(t.allowance(to_key(t.ali), spender), seed.into());
+    }
+    assert_eq!(t.named_key_count(), named_keys);
 }
 
 #[test]
@@ -189,7 +281,566 @@ fn test_burn_unauthorized() {
     );
 }
 
+#[test]
+fn test_burn_self() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    t.transfer(to_key(t.bob), 10.into(), Sender(t.ali));
+    t.burn_self(4.into(), Sender(t.bob));
+    assert_eq!(t.balance_of(to_key(t.bob)), 6.into());
+    assert_eq!(t.total_supply(), token_cfg::total_supply() - U256::from(4));
+}
+
+#[test]
+fn test_burn_self_too_much() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let bob = t.bob;
+    // Error::BurnAmountExceedsBalance
+    assert_user_error(2, || t.burn_self(1.into(), Sender(bob)));
+}
+
+#[test]
+fn test_burn_from() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    t.approve(to_key(t.bob), 10.into(), Sender(t.ali));
+    t.burn_from(to_key(t.ali), 4.into(), Sender(t.bob));
+    assert_eq!(t.balance_of(to_key(t.ali)), token_cfg::total_supply() - U256::from(4));
+    assert_eq!(t.allowance(to_key(t.ali), to_key(t.bob)), 6.into());
+    assert_eq!(t.total_supply(), token_cfg::total_supply() - U256::from(4));
+}
+
+#[test]
+fn test_burn_from_over_allowance() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let (ali, bob) = (t.ali, t.bob);
+    t.approve(to_key(bob), 3.into(), Sender(ali));
+    // Error::InsufficientAllowance
+    assert_user_error(9, || t.burn_from(to_key(ali), 4.into(), Sender(bob)));
+}
+
+#[test]
+fn test_disable_forced_burn() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let ali = t.ali;
+    t.disable_forced_burn(Sender(ali));
+    // Error::ForcedBurnDisabled
+    assert_user_error(11, || t.burn(to_key(ali), 1.into(), Sender(ali)));
+    t.burn_self(1.into(), Sender(ali));
+    assert_eq!(t.balance_of(to_key(ali)), token_cfg::total_supply() - U256::from(1));
+}
+
+#[test]
+fn test_disable_forced_burn_unauthorized() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let bob = t.bob;
+    // Error::NoAccessRights
+    assert_user_error(3, || t.disable_forced_burn(Sender(bob)));
+}
+
+#[test]
+fn test_pause_blocks_transfer() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let (ali, bob) = (t.ali, t.bob);
+    t.pause(Sender(ali));
+    assert!(t.paused());
+    let paused = t.event(t.event_count() - 1);
+    assert_eq!(paused.get("event_type").unwrap(), "paused");
+    // Error::Paused
+    assert_user_error(12, || t.transfer(to_key(bob), 1.into(), Sender(ali)));
+    t.unpause(Sender(ali));
+    assert!(!t.paused());
+    let unpaused = t.event(t.event_count() - 1);
+    assert_eq!(unpaused.get("event_type").unwrap(), "unpaused");
+    t.transfer(to_key(bob), 1.into(), Sender(ali));
+    assert_eq!(t.balance_of(to_key(bob)), 1.into());
+}
+
+#[test]
+fn test_pause_blocks_transfer_from() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let (ali, bob, joe) = (t.ali, t.bob, t.joe);
+    t.approve(to_key(bob), 5.into(), Sender(ali));
+    t.pause(Sender(ali));
+    // Error::Paused
+    assert_user_error(12, || t.transfer_from(to_key(ali), to_key(joe), 5.into(), Sender(bob)));
+}
+
+#[test]
+fn test_pause_blocks_mint() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let (ali, bob) = (t.ali, t.bob);
+    t.pause(Sender(ali));
+    // Error::Paused
+    assert_user_error(12, || t.mint(to_key(bob), 1.into(), Sender(ali)));
+}
+
+#[test]
+fn test_pause_unauthorized() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let bob = t.bob;
+    // Error::NoAccessRights
+    assert_user_error(3, || t.pause(Sender(bob)));
+}
+
+#[test]
+fn test_freeze_blocks_transfers() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let (ali, bob, joe) = (t.ali, t.bob, t.joe);
+    t.transfer(to_key(bob), 10.into(), Sender(ali));
+    t.freeze(to_key(bob), Sender(ali));
+    assert!(t.is_frozen(to_key(bob)));
+    // Error::AccountFrozen, both from and to the frozen key.
+    assert_user_error(13, || t.transfer(to_key(joe), 1.into(), Sender(bob)));
+    assert_user_error(13, || t.transfer(to_key(bob), 1.into(), Sender(ali)));
+    t.unfreeze(to_key(bob), Sender(ali));
+    t.transfer(to_key(joe), 1.into(), Sender(bob));
+    assert_eq!(t.balance_of(to_key(joe)), 1.into());
+}
+
+#[test]
+fn test_frozen_account_cannot_receive_mints() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let (ali, bob) = (t.ali, t.bob);
+    t.freeze(to_key(bob), Sender(ali));
+    // Error::AccountFrozen, minted and swapped-in tokens are held back like transfers.
+    assert_user_error(13, || t.mint(to_key(bob), 10.into(), Sender(ali)));
+    assert_user_error(13, || t.swap_in(SWAP_TX_HASH, to_key(bob), 10.into(), Sender(ali)));
+    assert_eq!(t.balance_of(to_key(bob)), 0.into());
+    t.unfreeze(to_key(bob), Sender(ali));
+    t.mint(to_key(bob), 10.into(), Sender(ali));
+    assert_eq!(t.balance_of(to_key(bob)), 10.into());
+}
+
+#[test]
+fn test_frozen_spender_cannot_use_allowance() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let (ali, bob, joe) = (t.ali, t.bob, t.joe);
+    t.approve(to_key(bob), 10.into(), Sender(ali));
+    t.freeze(to_key(bob), Sender(ali));
+    // Error::AccountFrozen, the owner and the recipient are not frozen but the spender is.
+    assert_user_error(13, || t.transfer_from(to_key(ali), to_key(joe), 5.into(), Sender(bob)));
+    assert_user_error(13, || t.burn_from(to_key(ali), 5.into(), Sender(bob)));
+    assert_eq!(t.allowance(to_key(ali), to_key(bob)), 10.into());
+    t.unfreeze(to_key(bob), Sender(ali));
+    t.transfer_from(to_key(ali), to_key(joe), 5.into(), Sender(bob));
+    assert_eq!(t.balance_of(to_key(joe)), 5.into());
+}
+
+#[test]
+fn test_frozen_account_cannot_swap_out() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let (ali, bob) = (t.ali, t.bob);
+    t.transfer(to_key(bob), 10.into(), Sender(ali));
+    t.freeze(to_key(bob), Sender(ali));
+    // Error::AccountFrozen
+    assert_user_error(13, || t.swap_out(10.into(), TO_ADDRESS, TO_CHAIN_ID.into(), Sender(bob)));
+    assert_user_error(13, || t.burn_self(10.into(), Sender(bob)));
+}
+
+#[test]
+fn test_wipe_frozen() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    t.transfer(to_key(t.bob), 10.into(), Sender(t.ali));
+    t.freeze(to_key(t.bob), Sender(t.ali));
+    t.wipe_frozen(to_key(t.bob), Sender(t.ali));
+    assert_eq!(t.balance_of(to_key(t.bob)), 0.into());
+    assert_eq!(t.total_supply(), token_cfg::total_supply() - U256::from(10));
+    let wiped = t.event(t.event_count() - 1);
+    assert_eq!(wiped.get("event_type").unwrap(), "wiped");
+    assert_eq!(wiped.get("amount").unwrap(), "10");
+}
+
+#[test]
+fn test_wipe_not_frozen() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let (ali, bob) = (t.ali, t.bob);
+    // Error::AccountNotFrozen
+    assert_user_error(14, || t.wipe_frozen(to_key(bob), Sender(ali)));
+}
+
+#[test]
+fn test_freeze_unauthorized() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let (ali, bob) = (t.ali, t.bob);
+    // Error::NoAccessRights
+    assert_user_error(3, || t.freeze(to_key(ali), Sender(bob)));
+}
+
+#[test]
+fn test_mint_up_to_cap() {
+    let cap = token_cfg::total_supply() + U256::from(10);
+    let mut t = Token::deployed_with_cap("ERC20", "ERC", Some(cap));
+    assert_eq!(t.cap(), Some(cap));
+    t.mint(to_key(t.bob), 10.into(), Sender(t.ali));
+    assert_eq!(t.total_supply(), cap);
+}
+
+#[test]
+fn test_mint_over_cap() {
+    let mut t = Token::deployed_with_cap("ERC20", "ERC", Some(token_cfg::total_supply() + U256::from(10)));
+    let (ali, bob) = (t.ali, t.bob);
+    // Error::CapExceeded
+    assert_user_error(15, || t.mint(to_key(bob), 11.into(), Sender(ali)));
+    assert_user_error(15, || t.swap_in(SWAP_TX_HASH, to_key(bob), 11.into(), Sender(ali)));
+}
+
+#[test]
+fn test_uncapped_by_default() {
+    let t = Token::deployed("ERC20", "ERC");
+    assert_eq!(t.cap(), None);
+}
+
+#[test]
+fn test_set_cap() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let (ali, bob) = (t.ali, t.bob);
+    t.set_cap(Some(token_cfg::total_supply()), Sender(ali));
+    // Error::CapExceeded
+    assert_user_error(15, || t.mint(to_key(bob), 1.into(), Sender(ali)));
+    assert_user_error(15, || t.set_cap(Some(token_cfg::total_supply() - U256::from(1)), Sender(ali)));
+    // Error::NoAccessRights
+    assert_user_error(3, || t.set_cap(None, Sender(bob)));
+    t.set_cap(None, Sender(ali));
+    t.mint(to_key(bob), 1.into(), Sender(ali));
+    assert_eq!(t.balance_of(to_key(bob)), 1.into());
+}
+
+const MINT_RATE_WINDOW: u64 = 86_400_000;
+
+#[test]
+fn test_minter_quota() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let (ali, bob, joe) = (t.ali, t.bob, t.joe);
+    t.grant_role("minter", to_key(bob), Sender(ali));
+    t.set_minter_limits(to_key(bob), Some(10.into()), None, Sender(ali));
+    t.mint(to_key(joe), 6.into(), Sender(bob));
+    assert_eq!(t.remaining_mint_limit(to_key(bob)), Some(4.into()));
+    // Error::MintQuotaExceeded
+    assert_user_error(16, || t.mint(to_key(joe), 5.into(), Sender(bob)));
+    t.mint(to_key(joe), 4.into(), Sender(bob));
+    assert_eq!(t.balance_of(to_key(joe)), 10.into());
+    assert_eq!(t.remaining_mint_limit(to_key(bob)), Some(0.into()));
+    assert_eq!(t.remaining_mint_limit(to_key(ali)), None);
+}
+
+#[test]
+fn test_minter_rate_limit() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let (ali, bob, joe) = (t.ali, t.bob, t.joe);
+    t.grant_role("minter", to_key(bob), Sender(ali));
+    t.set_minter_limits(to_key(bob), None, Some(10.into()), Sender(ali));
+    t.mint(to_key(joe), 10.into(), Sender(bob));
+    // Error::MintRateLimitExceeded
+    assert_user_error(17, || t.mint(to_key(joe), 1.into(), Sender(bob)));
+    // Half a window refills half the limit.
+    t.advance_block_time(MINT_RATE_WINDOW / 2);
+    assert_eq!(t.remaining_mint_limit(to_key(bob)), Some(5.into()));
+    t.mint(to_key(joe), 5.into(), Sender(bob));
+    assert_user_error(17, || t.mint(to_key(joe), 1.into(), Sender(bob)));
+    t.advance_block_time(MINT_RATE_WINDOW);
+    t.mint(to_key(joe), 10.into(), Sender(bob));
+    assert_eq!(t.balance_of(to_key(joe)), 25.into());
+}
+
+#[test]
+fn test_minter_rate_limit_max_value() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let (ali, bob, joe) = (t.ali, t.bob, t.joe);
+    t.grant_role("minter", to_key(bob), Sender(ali));
+    t.set_minter_limits(to_key(bob), None, Some(U256::MAX), Sender(ali));
+    t.mint(to_key(joe), 10.into(), Sender(bob));
+    // Scaling the refill by the elapsed time must not overflow.
+    t.advance_block_time(MINT_RATE_WINDOW / 2);
+    assert_eq!(t.remaining_mint_limit(to_key(bob)), Some(U256::MAX));
+    t.mint(to_key(joe), 10.into(), Sender(bob));
+    assert_eq!(t.balance_of(to_key(joe)), 20.into());
+}
+
+#[test]
+fn test_set_minter_limits_unauthorized() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let bob = t.bob;
+    // Error::NoAccessRights
+    assert_user_error(3, || t.set_minter_limits(to_key(bob), None, None, Sender(bob)));
+}
+
+#[test]
+fn test_events_initial_supply() {
+    let t = Token::deployed("ERC20", "ERC");
+    assert_eq!(t.event_count(), 1);
+    let event = t.event(0);
+    assert_eq!(event["event_type"], "mint");
+    assert_eq!(event["owner"], to_key(t.ali).to_formatted_string());
+    assert_eq!(event["amount"], token_cfg::total_supply().to_string());
+}
+
+#[test]
+fn test_events_transfer_and_approval() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    t.transfer(to_key(t.bob), 10.into(), Sender(t.ali));
+    t.approve(to_key(t.bob), 5.into(), Sender(t.ali));
+    t.transfer_from(to_key(t.ali), to_key(t.joe), 3.into(), Sender(t.bob));
+    assert_eq!(t.event_count(), 5);
+
+    let transfer = t.event(1);
+    assert_eq!(transfer["event_type"], "transfer");
+    assert_eq!(transfer["from"], to_key(t.ali).to_formatted_string());
+    assert_eq!(transfer["to"], to_key(t.bob).to_formatted_string());
+    assert_eq!(transfer["amount"], "10");
+
+    let approval = t.event(2);
+    assert_eq!(approval["event_type"], "approval");
+    assert_eq!(approval["owner"], to_key(t.ali).to_formatted_string());
+    assert_eq!(approval["spender"], to_key(t.bob).to_formatted_string());
+    assert_eq!(approval["allowance"], "5");
+
+    let transfer_from = t.event(3);
+    assert_eq!(transfer_from["event_type"], "transfer");
+    assert_eq!(transfer_from["to"], to_key(t.joe).to_formatted_string());
+    assert_eq!(transfer_from["amount"], "3");
+
+    // transfer_from lowers the spender's allowance and records it.
+    let allowance_update = t.event(4);
+    assert_eq!(allowance_update["event_type"], "approval");
+    assert_eq!(allowance_update["allowance"], "2");
+}
+
+#[test]
+fn test_events_mint_and_burn() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    t.mint(to_key(t.bob), 10.into(), Sender(t.ali));
+    t.burn(to_key(t.bob), 4.into(), Sender(t.ali));
+    assert_eq!(t.event_count(), 3);
+
+    let mint = t.event(1);
+    assert_eq!(mint["event_type"], "mint");
+    assert_eq!(mint["owner"], to_key(t.bob).to_formatted_string());
+    assert_eq!(mint["amount"], "10");
+
+    let burn = t.event(2);
+    assert_eq!(burn["event_type"], "burn");
+    assert_eq!(burn["owner"], to_key(t.bob).to_formatted_string());
+    assert_eq!(burn["amount"], "4");
+}
+
+const SWAP_TX_HASH: &str = "0x2b8a4a1c8fe0a3b0e4e5f3b1b9a53a1d1d6e33d8e6f3a8b5c7a19c5a0b21b3e4";
+
+#[test]
+fn test_swap_in() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let amount = 10.into();
+    let total_supply = t.total_supply();
+    t.swap_in(SWAP_TX_HASH, to_key(t.bob), amount, Sender(t.ali));
+    assert_eq!(t.balance_of(to_key(t.bob)), amount);
+    assert_eq!(t.total_supply(), total_supply + amount);
+    let event = t.event(t.event_count() - 1);
+    assert_eq!(event["event_type"], "swap_in");
+    assert_eq!(event["tx_hash"], SWAP_TX_HASH);
+    assert_eq!(event["account"], to_key(t.bob).to_formatted_string());
+    assert_eq!(event["amount"], "10");
+}
+
+#[test]
+fn test_swap_in_replay() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let (ali, bob) = (t.ali, t.bob);
+    t.swap_in(SWAP_TX_HASH, to_key(bob), 10.into(), Sender(ali));
+    // Error::SwapInAlreadyProcessed
+    assert_user_error(4, || t.swap_in(SWAP_TX_HASH, to_key(bob), 10.into(), Sender(ali)));
+}
+
+#[test]
+fn test_swap_in_unauthorized() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let bob = t.bob;
+    // Error::NoAccessRights
+    assert_user_error(3, || t.swap_in(SWAP_TX_HASH, to_key(bob), 10.into(), Sender(bob)));
+}
+
+#[test]
+fn test_swap_out() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let bind_address = "0x7Be8076f4EA4A4AD08075C2508e481d6C946D12b";
+    t.transfer(to_key(t.bob), 10.into(), Sender(t.ali));
+    let total_supply = t.total_supply();
+    t.swap_out(4.into(), bind_address, 56.into(), Sender(t.bob));
+    assert_eq!(t.balance_of(to_key(t.bob)), 6.into());
+    assert_eq!(t.total_supply(), total_supply - U256::from(4));
+    let event = t.event(t.event_count() - 1);
+    assert_eq!(event["event_type"], "swap_out");
+    assert_eq!(event["account"], to_key(t.bob).to_formatted_string());
+    assert_eq!(event["amount"], "4");
+    assert_eq!(event["bind_address"], bind_address);
+    assert_eq!(event["to_chain_id"], "56");
+}
+
+#[test]
+fn test_swap_out_too_much() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let bob = t.bob;
+    // Error::BurnAmountExceedsBalance
+    assert_user_error(2, || t.swap_out(1.into(), "0x7Be8076f4EA4A4AD08075C2508e481d6C946D12b", 56.into(), Sender(bob)));
+}
+
+const GOVERNANCE_DELAY: u64 = 172_800_000;
+
+#[test]
+fn test_governance_rotation() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    t.propose_governance(Key::Account(t.bob), Sender(t.ali));
+    assert_eq!(t.pending_governance(), Some(Key::Account(t.bob)));
+    assert_eq!(t.governance(), Key::Account(t.ali));
+    t.advance_block_time(GOVERNANCE_DELAY);
+    t.apply_governance(Sender(t.bob));
+    assert_eq!(t.governance(), Key::Account(t.bob));
+    assert_eq!(t.pending_governance(), None);
+    t.mint(to_key(t.bob), 10.into(), Sender(t.bob));
+    assert_eq!(t.balance_of(to_key(t.bob)), 10.into());
+}
+
+#[test]
+fn test_apply_governance_before_delay() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let (ali, bob) = (t.ali, t.bob);
+    t.propose_governance(Key::Account(bob), Sender(ali));
+    t.advance_block_time(GOVERNANCE_DELAY - 1);
+    // Error::GovernanceDelayNotPassed
+    assert_user_error(6, || t.apply_governance(Sender(bob)));
+}
+
+#[test]
+fn test_apply_governance_not_pending() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let (ali, bob) = (t.ali, t.bob);
+    // Error::NoPendingGovernance
+    assert_user_error(5, || t.apply_governance(Sender(ali)));
+    t.propose_governance(Key::Account(bob), Sender(ali));
+    t.advance_block_time(GOVERNANCE_DELAY);
+    // Error::NoAccessRights, only the pending key itself can apply
+    assert_user_error(3, || t.apply_governance(Sender(ali)));
+}
+
+#[test]
+fn test_propose_governance_unauthorized() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let bob = t.bob;
+    // Error::NoAccessRights
+    assert_user_error(3, || t.propose_governance(Key::Account(bob), Sender(bob)));
+}
+
+#[test]
+fn test_old_governance_revoked() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let (ali, bob) = (t.ali, t.bob);
+    t.propose_governance(Key::Account(bob), Sender(ali));
+    t.advance_block_time(GOVERNANCE_DELAY);
+    t.apply_governance(Sender(bob));
+    // Error::NoAccessRights
+    assert_user_error(3, || t.mint(to_key(ali), 10.into(), Sender(ali)));
+}
+
+#[test]
+fn test_contract_governance_mints() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let (proxy, proxy_key) = t.deploy_governance_proxy(Sender(t.ali));
+    t.propose_governance(proxy_key, Sender(t.ali));
+    t.advance_block_time(GOVERNANCE_DELAY);
+    t.proxy_apply_governance(proxy, Sender(t.ali));
+    assert_eq!(t.governance(), proxy_key);
+    t.proxy_mint(proxy, to_key(t.bob), 10.into(), Sender(t.ali));
+    assert_eq!(t.balance_of(to_key(t.bob)), 10.into());
+}
+
+#[test]
+fn test_contract_mint_without_governance() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let (ali, bob) = (t.ali, t.bob);
+    let (proxy, _) = t.deploy_governance_proxy(Sender(ali));
+    // Error::NoAccessRights
+    assert_user_error(3, || t.proxy_mint(proxy, to_key(bob), 10.into(), Sender(ali)));
+}
+
+#[test]
+fn test_governance_holds_every_role() {
+    let t = Token::deployed("ERC20", "ERC");
+    for role in ["admin", "minter", "burner", "pauser"].iter() {
+        assert!(t.has_role(role, to_key(t.ali)));
+        assert!(!t.has_role(role, to_key(t.bob)));
+    }
+}
+
+#[test]
+fn test_grant_minter_role() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    t.grant_role("minter", to_key(t.bob), Sender(t.ali));
+    assert!(t.has_role("minter", to_key(t.bob)));
+    let event = t.event(t.event_count() - 1);
+    assert_eq!(event.get("event_type").unwrap(), "role_granted");
+    assert_eq!(event.get("role").unwrap(), "minter");
+    t.mint(to_key(t.joe), 10.into(), Sender(t.bob));
+    assert_eq!(t.balance_of(to_key(t.joe)), 10.into());
+}
+
+#[test]
+fn test_minter_cannot_burn() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let (ali, bob) = (t.ali, t.bob);
+    t.grant_role("minter", to_key(bob), Sender(ali));
+    // Error::NoAccessRights
+    assert_user_error(3, || t.burn(to_key(ali), 10.into(), Sender(bob)));
+}
+
+#[test]
+fn test_grant_role_unauthorized() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let bob = t.bob;
+    // Error::NoAccessRights
+    assert_user_error(3, || t.grant_role("minter", to_key(bob), Sender(bob)));
+}
+
+#[test]
+fn test_grant_unknown_role() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let (ali, bob) = (t.ali, t.bob);
+    // Error::InvalidRole
+    assert_user_error(7, || t.grant_role("owner", to_key(bob), Sender(ali)));
+}
+
+#[test]
+fn test_revoked_minter_cannot_mint() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    let (ali, bob, joe) = (t.ali, t.bob, t.joe);
+    t.grant_role("minter", to_key(bob), Sender(ali));
+    t.revoke_role("minter", to_key(bob), Sender(ali));
+    assert!(!t.has_role("minter", to_key(bob)));
+    // Error::NoAccessRights
+    assert_user_error(3, || t.mint(to_key(joe), 10.into(), Sender(bob)));
+}
+
+#[test]
+fn test_renounce_role() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    t.renounce_role("burner", Sender(t.ali));
+    assert!(!t.has_role("burner", to_key(t.ali)));
+    assert!(t.has_role("minter", to_key(t.ali)));
+}
+
+#[test]
+fn test_governance_rotation_moves_roles() {
+    let mut t = Token::deployed("ERC20", "ERC");
+    t.propose_governance(Key::Account(t.bob), Sender(t.ali));
+    t.advance_block_time(GOVERNANCE_DELAY);
+    t.apply_governance(Sender(t.bob));
+    assert!(t.has_role("admin", to_key(t.bob)));
+    assert!(!t.has_role("admin", to_key(t.ali)));
+}
+
 // ------------ START - CsprHolder Tests ------------
+const TO_CHAIN_ID: u64 = 56;
+const TO_ADDRESS: &str = "0x7Be8076f4EA4A4AD08075C2508e481d6C946D12b";
+const UNLOCK_CAP_WINDOW: u64 = 86_400_000;
+const LARGE_UNLOCK_DELAY: u64 = 86_400_000;
+const REFUND_TIMEOUT: u64 = 604_800_000;
+
 #[test]
 fn test_cspr_holder_deploy() {
     let b = CsprHolder::deployed();
@@ -197,28 +848,494 @@ fn test_cspr_holder_deploy() {
 }
 
 #[test]
-#[should_panic]
 fn test_lock_zero() {
     let mut b = CsprHolder::deployed();
-    let amount: U512 = 0.into();
-    let main_purse = URef::new(b.ali.value(), AccessRights::READ_ADD_WRITE);
-    b.lock(
+    let ali = b.ali;
+    let main_purse = b.main_purse(ali);
+    // Error::DepositAmountTooSmall
+    assert_user_error(0, || b.lock(
         main_purse,
+        0.into(),
+        TO_CHAIN_ID.into(),
+        TO_ADDRESS,
+        CSPR_Sender(ali)
+    ));
+}
+
+#[test]
+fn test_unlock_not_owner() {
+    let mut b = CsprHolder::deployed();
+    let bob = b.bob;
+    // Error::NotASigner
+    assert_user_error(3, || b.approve_unlock(
+        "tx-1",
+        PublicKey::ed25519_from_bytes([6u8; 32]).unwrap(),
+        10.into(),
+        CSPR_Sender(bob)
+    ));
+}
+
+#[test]
+fn test_lock_records_deposits() {
+    let mut b = CsprHolder::deployed();
+    assert_eq!(b.deposit_count(), 0);
+    b.lock(
+        b.main_purse(b.ali),
+        1_000.into(),
+        TO_CHAIN_ID.into(),
+        TO_ADDRESS,
+        CSPR_Sender(b.ali)
+    );
+    b.lock(
+        b.main_purse(b.bob),
+        500.into(),
+        1.into(),
+        "0x0000000000000000000000000000000000000001",
+        CSPR_Sender(b.bob)
+    );
+    assert_eq!(b.deposit_count(), 2);
+    assert_eq!(b.total_locked(), 1_500.into());
+
+    let deposit = b.deposit(0);
+    assert_eq!(deposit["depositor"], Key::Account(b.ali).to_formatted_string());
+    assert_eq!(deposit["amount"], "1000");
+    assert_eq!(deposit["to_chain_id"], TO_CHAIN_ID.to_string());
+    assert_eq!(deposit["to_address"], TO_ADDRESS);
+    assert!(deposit.contains_key("timestamp"));
+
+    let deposit = b.deposit(1);
+    assert_eq!(deposit["depositor"], Key::Account(b.bob).to_formatted_string());
+    assert_eq!(deposit["amount"], "500");
+    assert_eq!(deposit["to_chain_id"], "1");
+}
+
+#[test]
+fn test_deposit_session() {
+    let mut b = CsprHolder::deployed();
+    let amount: U512 = 1_000_000.into();
+    let bob_balance = b.purse_balance(b.main_purse(b.bob));
+    b.lock_with_session(
+        amount,
+        TO_CHAIN_ID.into(),
+        TO_ADDRESS,
+        CSPR_Sender(b.bob)
+    );
+    // The deploy's payment comes out of the same purse, so only a lower bound holds.
+    assert!(bob_balance - b.purse_balance(b.main_purse(b.bob)) >= amount);
+    assert_eq!(b.total_locked(), amount);
+    let deposit = b.deposit(0);
+    assert_eq!(deposit["depositor"], Key::Account(b.bob).to_formatted_string());
+    assert_eq!(deposit["amount"], amount.to_string());
+
+    // The contract purse holds the deposit: releasing it to a fresh account pays it in full.
+    let target = PublicKey::ed25519_from_bytes([12u8; 32]).unwrap();
+    b.approve_unlock(
+        "tx-1",
+        target.clone(),
         amount,
         CSPR_Sender(b.ali)
     );
+    assert_eq!(b.balance_of(target.to_account_hash()), amount);
 }
 
 #[test]
-#[should_panic]
-fn test_unlock_not_owner() {
+fn test_unlock() {
     let mut b = CsprHolder::deployed();
-    let amount: U512 = 10.into();
-    b.unlock(
+    let amount: U512 = 1_000.into();
+    b.lock(
+        b.main_purse(b.ali),
+        amount,
+        TO_CHAIN_ID.into(),
+        TO_ADDRESS,
+        CSPR_Sender(b.ali)
+    );
+    let bob_balance = b.purse_balance(b.main_purse(b.bob));
+    b.approve_unlock(
+        "tx-1",
         PublicKey::ed25519_from_bytes([6u8; 32]).unwrap(),
         amount,
-        CSPR_Sender(b.bob)
+        CSPR_Sender(b.ali)
+    );
+    assert_eq!(b.purse_balance(b.main_purse(b.bob)), bob_balance + amount);
+}
+
+#[test]
+fn test_unlock_replay() {
+    let mut b = CsprHolder::deployed();
+    let ali = b.ali;
+    let amount: U512 = 1_000.into();
+    b.lock(
+        b.main_purse(b.ali),
+        amount * U512::from(2),
+        TO_CHAIN_ID.into(),
+        TO_ADDRESS,
+        CSPR_Sender(b.ali)
+    );
+    b.approve_unlock(
+        "tx-1",
+        PublicKey::ed25519_from_bytes([6u8; 32]).unwrap(),
+        amount,
+        CSPR_Sender(b.ali)
     );
+    // Same source transaction, funds still available: must revert anyway.
+    // Error::UnlockAlreadyProcessed
+    assert_user_error(2, || b.approve_unlock(
+        "tx-1",
+        PublicKey::ed25519_from_bytes([6u8; 32]).unwrap(),
+        amount,
+        CSPR_Sender(ali)
+    ));
+}
+
+#[test]
+fn test_cspr_holder_accounting() {
+    let mut b = CsprHolder::deployed();
+    let (ali, bob) = (b.ali, b.bob);
+    b.lock(b.main_purse(ali), 1_000.into(), TO_CHAIN_ID.into(), TO_ADDRESS, CSPR_Sender(ali));
+    b.lock(b.main_purse(bob), 300.into(), TO_CHAIN_ID.into(), TO_ADDRESS, CSPR_Sender(bob));
+    b.lock(b.main_purse(ali), 200.into(), TO_CHAIN_ID.into(), TO_ADDRESS, CSPR_Sender(ali));
+    assert_eq!(b.deposits_of(Key::Account(ali)), 1_200.into());
+    assert_eq!(b.deposits_of(Key::Account(bob)), 300.into());
+    let target = PublicKey::ed25519_from_bytes([12u8; 32]).unwrap();
+    b.approve_unlock("tx-1", target, 400.into(), CSPR_Sender(ali));
+    assert_eq!(b.total_locked(), 1_500.into());
+    assert_eq!(b.total_unlocked(), 400.into());
+    let purse = b.contract_purse();
+    assert_eq!(purse.access_rights(), AccessRights::READ);
+    assert_eq!(b.purse_balance(purse), 1_100.into());
+    b.reconcile(CSPR_Sender(bob));
+}
+
+#[test]
+fn test_cspr_holder_pause_blocks_lock() {
+    let mut b = CsprHolder::deployed();
+    let ali = b.ali;
+    b.pause(CSPR_Sender(ali));
+    assert!(b.paused());
+    assert_eq!(b.event(0).get("event_type").unwrap(), "paused");
+    let purse = b.main_purse(ali);
+    // Error::Paused
+    assert_user_error(10, || b.lock(purse, 1_000.into(), TO_CHAIN_ID.into(), TO_ADDRESS, CSPR_Sender(ali)));
+    b.unpause(CSPR_Sender(ali));
+    assert_eq!(b.event(1).get("event_type").unwrap(), "unpaused");
+    b.lock(purse, 1_000.into(), TO_CHAIN_ID.into(), TO_ADDRESS, CSPR_Sender(ali));
+    assert_eq!(b.total_locked(), 1_000.into());
+}
+
+#[test]
+fn test_cspr_holder_pause_blocks_unlock() {
+    let mut b = CsprHolder::deployed();
+    let ali = b.ali;
+    b.lock(b.main_purse(ali), 1_000.into(), TO_CHAIN_ID.into(), TO_ADDRESS, CSPR_Sender(ali));
+    b.pause(CSPR_Sender(ali));
+    let target = PublicKey::ed25519_from_bytes([6u8; 32]).unwrap();
+    // Error::Paused
+    assert_user_error(10, || b.approve_unlock("tx-1", target.clone(), 1_000.into(), CSPR_Sender(ali)));
+}
+
+#[test]
+fn test_cspr_holder_pause_unauthorized() {
+    let mut b = CsprHolder::deployed();
+    let bob = b.bob;
+    // Error::NoAccessRights
+    assert_user_error(1, || b.pause(CSPR_Sender(bob)));
+}
+
+/// deploys a holder with 1_000 motes locked and ali, bob and joe as signers.
+fn holder_with_signers(threshold: u32) -> CsprHolder {
+    let mut b = CsprHolder::deployed();
+    b.lock(
+        b.main_purse(b.ali),
+        1_000.into(),
+        TO_CHAIN_ID.into(),
+        TO_ADDRESS,
+        CSPR_Sender(b.ali)
+    );
+    b.add_signer(Key::Account(b.bob), CSPR_Sender(b.ali));
+    b.add_signer(Key::Account(b.joe), CSPR_Sender(b.ali));
+    b.set_threshold(threshold, CSPR_Sender(b.ali));
+    b
+}
+
+#[test]
+fn test_approve_unlock_threshold() {
+    let mut b = holder_with_signers(2);
+    let target = PublicKey::ed25519_from_bytes([12u8; 32]).unwrap();
+    let amount: U512 = 1_000.into();
+    b.approve_unlock("tx-1", target.clone(), amount, CSPR_Sender(b.ali));
+    assert_eq!(b.balance_of(target.to_account_hash()), 0.into());
+    b.approve_unlock("tx-1", target.clone(), amount, CSPR_Sender(b.bob));
+    assert_eq!(b.balance_of(target.to_account_hash()), amount);
+}
+
+#[test]
+fn test_approve_unlock_mismatched_requests() {
+    let mut b = holder_with_signers(2);
+    let target = PublicKey::ed25519_from_bytes([12u8; 32]).unwrap();
+    // Same tx id but a different amount: the approvals must not be combined.
+    b.approve_unlock("tx-1", target.clone(), 1_000.into(), CSPR_Sender(b.ali));
+    b.approve_unlock("tx-1", target.clone(), 900.into(), CSPR_Sender(b.bob));
+    assert_eq!(b.balance_of(target.to_account_hash()), 0.into());
+    b.approve_unlock("tx-1", target.clone(), 1_000.into(), CSPR_Sender(b.joe));
+    assert_eq!(b.balance_of(target.to_account_hash()), 1_000.into());
+}
+
+#[test]
+fn test_approve_unlock_duplicate_approval() {
+    let mut b = holder_with_signers(2);
+    let ali = b.ali;
+    let target = PublicKey::ed25519_from_bytes([12u8; 32]).unwrap();
+    b.approve_unlock("tx-1", target.clone(), 1_000.into(), CSPR_Sender(ali));
+    // Error::AlreadyApproved
+    assert_user_error(4, || b.approve_unlock("tx-1", target.clone(), 1_000.into(), CSPR_Sender(ali)));
+}
+
+#[test]
+fn test_approve_unlock_after_execution() {
+    let mut b = holder_with_signers(2);
+    let joe = b.joe;
+    let target = PublicKey::ed25519_from_bytes([12u8; 32]).unwrap();
+    b.approve_unlock("tx-1", target.clone(), 500.into(), CSPR_Sender(b.ali));
+    b.approve_unlock("tx-1", target.clone(), 500.into(), CSPR_Sender(b.bob));
+    // Error::UnlockAlreadyProcessed
+    assert_user_error(2, || b.approve_unlock("tx-1", target.clone(), 500.into(), CSPR_Sender(joe)));
+}
+
+#[test]
+fn test_approve_unlock_threshold_raised_mid_flight() {
+    let mut b = holder_with_signers(2);
+    let target = PublicKey::ed25519_from_bytes([12u8; 32]).unwrap();
+    let amount: U512 = 1_000.into();
+    b.approve_unlock("tx-1", target.clone(), amount, CSPR_Sender(b.ali));
+    b.set_threshold(3, CSPR_Sender(b.ali));
+    b.approve_unlock("tx-1", target.clone(), amount, CSPR_Sender(b.bob));
+    assert_eq!(b.balance_of(target.to_account_hash()), 0.into());
+    b.approve_unlock("tx-1", target.clone(), amount, CSPR_Sender(b.joe));
+    assert_eq!(b.balance_of(target.to_account_hash()), amount);
+}
+
+#[test]
+fn test_approve_unlock_removed_signer_no_longer_counts() {
+    let mut b = holder_with_signers(2);
+    let target = PublicKey::ed25519_from_bytes([12u8; 32]).unwrap();
+    let amount: U512 = 1_000.into();
+    b.approve_unlock("tx-1", target.clone(), amount, CSPR_Sender(b.bob));
+    b.remove_signer(Key::Account(b.bob), CSPR_Sender(b.ali));
+    b.approve_unlock("tx-1", target.clone(), amount, CSPR_Sender(b.ali));
+    assert_eq!(b.balance_of(target.to_account_hash()), 0.into());
+    b.approve_unlock("tx-1", target.clone(), amount, CSPR_Sender(b.joe));
+    assert_eq!(b.balance_of(target.to_account_hash()), amount);
+}
+
+#[test]
+fn test_approve_unlock_not_a_signer() {
+    let mut b = CsprHolder::deployed();
+    let joe = b.joe;
+    // Error::NotASigner
+    assert_user_error(3, || b.approve_unlock(
+        "tx-1",
+        PublicKey::ed25519_from_bytes([12u8; 32]).unwrap(),
+        10.into(),
+        CSPR_Sender(joe)
+    ));
+}
+
+#[test]
+fn test_set_threshold_above_signer_count() {
+    let mut b = CsprHolder::deployed();
+    let ali = b.ali;
+    // Error::InvalidThreshold
+    assert_user_error(5, || b.set_threshold(2, CSPR_Sender(ali)));
+}
+
+#[test]
+fn test_add_signer_unauthorized() {
+    let mut b = CsprHolder::deployed();
+    let bob = b.bob;
+    // Error::NoAccessRights
+    assert_user_error(1, || b.add_signer(Key::Account(bob), CSPR_Sender(bob)));
+}
+
+#[test]
+fn test_unlock_to_account_hash() {
+    let mut b = holder_with_signers(1);
+    let ali = b.ali;
+    let target = PublicKey::ed25519_from_bytes([12u8; 32]).unwrap().to_account_hash();
+    b.approve_unlock_to("tx-1", Key::Account(target), 400.into(), CSPR_Sender(ali));
+    assert_eq!(b.balance_of(target), 400.into());
+}
+
+#[test]
+fn test_unlock_to_purse() {
+    let mut b = holder_with_signers(1);
+    let ali = b.ali;
+    let (_, purse) = b.deploy_purse_receiver(CSPR_Sender(ali));
+    b.approve_unlock_to("tx-1", Key::URef(purse), 400.into(), CSPR_Sender(ali));
+    assert_eq!(b.purse_balance(purse), 400.into());
+    // Error::UnlockPurseNotAddable
+    assert_user_error(18, || b.approve_unlock_to("tx-2", Key::URef(purse.into_read()), 400.into(), CSPR_Sender(ali)));
+}
+
+#[test]
+fn test_unlock_to_contract() {
+    let mut b = holder_with_signers(1);
+    let ali = b.ali;
+    let (receiver, purse) = b.deploy_purse_receiver(CSPR_Sender(ali));
+    b.approve_unlock_to("tx-1", receiver, 400.into(), CSPR_Sender(ali));
+    assert_eq!(b.purse_balance(purse), 400.into());
+    assert_eq!(b.total_unlocked(), 400.into());
+}
+
+#[test]
+fn test_unlock_to_unsupported_target() {
+    let mut b = holder_with_signers(1);
+    let ali = b.ali;
+    // Error::UnsupportedUnlockTarget
+    assert_user_error(17, || b.approve_unlock_to("tx-1", Key::Balance([7u8; 32]), 400.into(), CSPR_Sender(ali)));
+}
+
+#[test]
+fn test_unlock_min_max_amount() {
+    let mut b = holder_with_signers(1);
+    let ali = b.ali;
+    let target = PublicKey::ed25519_from_bytes([12u8; 32]).unwrap();
+    b.set_unlock_limits(100.into(), Some(500.into()), None, None, CSPR_Sender(ali));
+    // Error::UnlockAmountTooSmall
+    assert_user_error(11, || b.approve_unlock("tx-1", target.clone(), 99.into(), CSPR_Sender(ali)));
+    // Error::UnlockAmountTooLarge
+    assert_user_error(12, || b.approve_unlock("tx-2", target.clone(), 501.into(), CSPR_Sender(ali)));
+    b.approve_unlock("tx-3", target.clone(), 500.into(), CSPR_Sender(ali));
+    assert_eq!(b.balance_of(target.to_account_hash()), 500.into());
+}
+
+#[test]
+fn test_unlock_daily_cap_refills() {
+    let mut b = holder_with_signers(1);
+    let ali = b.ali;
+    let target = PublicKey::ed25519_from_bytes([12u8; 32]).unwrap();
+    b.set_unlock_limits(0.into(), None, Some(600.into()), None, CSPR_Sender(ali));
+    b.approve_unlock("tx-1", target.clone(), 400.into(), CSPR_Sender(ali));
+    // Error::DailyUnlockCapExceeded
+    assert_user_error(13, || b.approve_unlock("tx-2", target.clone(), 300.into(), CSPR_Sender(ali)));
+    // Half a window refills half the cap.
+    b.advance_block_time(UNLOCK_CAP_WINDOW / 2);
+    b.approve_unlock("tx-2", target.clone(), 300.into(), CSPR_Sender(ali));
+    assert_eq!(b.balance_of(target.to_account_hash()), 700.into());
+}
+
+#[test]
+fn test_large_unlock_timelock() {
+    let mut b = holder_with_signers(1);
+    let (ali, bob) = (b.ali, b.bob);
+    let target = PublicKey::ed25519_from_bytes([12u8; 32]).unwrap();
+    b.set_unlock_limits(0.into(), None, None, Some(500.into()), CSPR_Sender(ali));
+    b.approve_unlock("tx-1", target.clone(), 800.into(), CSPR_Sender(ali));
+    assert_eq!(b.balance_of(target.to_account_hash()), 0.into());
+    assert_eq!(b.event(0).get("event_type").unwrap(), "unlock_queued");
+    // Error::UnlockTimelockNotPassed
+    assert_user_error(15, || b.execute_unlock("tx-1", CSPR_Sender(bob)));
+    b.advance_block_time(LARGE_UNLOCK_DELAY);
+    b.execute_unlock("tx-1", CSPR_Sender(bob));
+    assert_eq!(b.balance_of(target.to_account_hash()), 800.into());
+    // Error::UnlockNotQueued
+    assert_user_error(14, || b.execute_unlock("tx-1", CSPR_Sender(bob)));
+}
+
+#[test]
+fn test_large_unlock_cancelled() {
+    let mut b = holder_with_signers(1);
+    let (ali, bob) = (b.ali, b.bob);
+    let target = PublicKey::ed25519_from_bytes([12u8; 32]).unwrap();
+    b.set_unlock_limits(0.into(), None, None, Some(500.into()), CSPR_Sender(ali));
+    b.approve_unlock("tx-1", target.clone(), 800.into(), CSPR_Sender(ali));
+    // Error::NoAccessRights
+    assert_user_error(1, || b.cancel_unlock("tx-1", CSPR_Sender(bob)));
+    b.cancel_unlock("tx-1", CSPR_Sender(ali));
+    assert_eq!(b.event(1).get("event_type").unwrap(), "unlock_cancelled");
+    b.advance_block_time(LARGE_UNLOCK_DELAY);
+    // Error::UnlockNotQueued
+    assert_user_error(14, || b.execute_unlock("tx-1", CSPR_Sender(bob)));
+    // Error::UnlockAlreadyProcessed
+    assert_user_error(2, || b.approve_unlock("tx-1", target.clone(), 800.into(), CSPR_Sender(ali)));
+    assert_eq!(b.balance_of(target.to_account_hash()), 0.into());
+}
+
+#[test]
+fn test_refund_after_timeout() {
+    let mut b = CsprHolder::deployed();
+    let (ali, bob, joe) = (b.ali, b.bob, b.joe);
+    b.lock(b.main_purse(joe), 1_000.into(), TO_CHAIN_ID.into(), TO_ADDRESS, CSPR_Sender(joe));
+    assert_eq!(b.deposit(0)["status"], "pending");
+    // Error::RefundsDisabled
+    assert_user_error(25, || b.refund(0, CSPR_Sender(bob)));
+    b.set_refund_timeout(Some(REFUND_TIMEOUT), CSPR_Sender(ali));
+    // Error::RefundTimeoutNotPassed
+    assert_user_error(26, || b.refund(0, CSPR_Sender(bob)));
+    b.advance_block_time(REFUND_TIMEOUT);
+    // Refunds are not unlocks, the daily unlock cap doesn't apply to them.
+    b.set_unlock_limits(1.into(), None, Some(500.into()), None, CSPR_Sender(ali));
+    let balance = b.balance_of(joe);
+    b.refund(0, CSPR_Sender(bob));
+    assert_eq!(b.balance_of(joe), balance + 1_000);
+    assert_eq!(b.deposits_of(Key::Account(joe)), 0.into());
+    assert_eq!(b.total_locked(), 0.into());
+    assert_eq!(b.total_unlocked(), 0.into());
+    assert_eq!(b.deposit(0)["status"], "refunded");
+    assert_eq!(b.event(0).get("event_type").unwrap(), "deposit_refunded");
+    // Error::DepositNotPending
+    assert_user_error(24, || b.refund(0, CSPR_Sender(bob)));
+    b.reconcile(CSPR_Sender(bob));
+}
+
+#[test]
+fn test_completed_deposit_cannot_be_refunded() {
+    let mut b = CsprHolder::deployed();
+    let (ali, bob) = (b.ali, b.bob);
+    b.lock(b.main_purse(ali), 1_000.into(), TO_CHAIN_ID.into(), TO_ADDRESS, CSPR_Sender(ali));
+    b.set_refund_timeout(Some(REFUND_TIMEOUT), CSPR_Sender(ali));
+    // Error::NoAccessRights
+    assert_user_error(1, || b.complete_deposit(0, CSPR_Sender(bob)));
+    b.complete_deposit(0, CSPR_Sender(ali));
+    assert_eq!(b.deposit(0)["status"], "completed");
+    b.advance_block_time(REFUND_TIMEOUT);
+    // Error::DepositNotPending
+    assert_user_error(24, || b.refund(0, CSPR_Sender(bob)));
+    // Error::DepositNotPending, never recorded
+    assert_user_error(24, || b.complete_deposit(1, CSPR_Sender(ali)));
+}
+
+#[test]
+fn test_cspr_holder_governance_rotation() {
+    let mut b = CsprHolder::deployed();
+    b.propose_governance(Key::Account(b.bob), CSPR_Sender(b.ali));
+    b.advance_block_time(GOVERNANCE_DELAY);
+    b.apply_governance(CSPR_Sender(b.bob));
+    assert_eq!(b.governance(), Key::Account(b.bob));
+    b.add_signer(Key::Account(b.joe), CSPR_Sender(b.bob));
+}
+
+#[test]
+fn test_cspr_holder_apply_governance_before_delay() {
+    let mut b = CsprHolder::deployed();
+    let (ali, bob) = (b.ali, b.bob);
+    b.propose_governance(Key::Account(bob), CSPR_Sender(ali));
+    // Error::GovernanceDelayNotPassed
+    assert_user_error(9, || b.apply_governance(CSPR_Sender(bob)));
+}
+
+#[test]
+fn test_cspr_holder_contract_governance() {
+    let mut b = CsprHolder::deployed();
+    let ali = b.ali;
+    let (proxy, proxy_key) = b.deploy_governance_proxy(CSPR_Sender(ali));
+    b.propose_governance(proxy_key, CSPR_Sender(ali));
+    b.advance_block_time(GOVERNANCE_DELAY);
+    b.proxy_execute(proxy, "apply_governance", runtime_args! {}, CSPR_Sender(ali));
+    assert_eq!(b.governance(), proxy_key);
+    // Error::NoAccessRights, the previous account governance is out
+    assert_user_error(1, || b.pause(CSPR_Sender(ali)));
+    b.proxy_execute(proxy, "pause", runtime_args! {}, CSPR_Sender(ali));
+    assert!(b.paused());
 }
 
 // ------------ START - Factory Tests ------------
@@ -240,7 +1357,7 @@ fn test_factory_create_erc20() {
         token_cfg::SYMBOL.to_string(),
         token_cfg::DECIMALS,
         token_cfg::total_supply(),
-        f.ali,
+        Key::Account(f.ali),
         F_Sender(f.ali)
     );
     let contract_hash = f.get_erc20_hash(
@@ -263,7 +1380,7 @@ fn test_factory_create_multiple_erc20() {
         token_cfg::SYMBOL.to_string(),
         token_cfg::DECIMALS,
         token_cfg::total_supply(),
-        f.ali,
+        Key::Account(f.ali),
         F_Sender(f.ali)
     );
     let contract_hash = f.get_erc20_hash(
@@ -281,7 +1398,7 @@ fn test_factory_create_multiple_erc20() {
         "DAI".to_string(),
         token_cfg::DECIMALS,
         token_cfg::total_supply(),
-        f.ali,
+        Key::Account(f.ali),
         F_Sender(f.ali)
     );
     let contract_hash = f.get_erc20_hash(
@@ -299,7 +1416,7 @@ fn test_factory_create_unauthorized() {
         token_cfg::SYMBOL.to_string(),
         token_cfg::DECIMALS,
         token_cfg::total_supply(),
-        f.ali,
+        Key::Account(f.ali),
         F_Sender(f.bob)
     );
 }
@@ -313,7 +1430,7 @@ fn test_factory_create_redundant() {
         token_cfg::SYMBOL.to_string(),
         token_cfg::DECIMALS,
         token_cfg::total_supply(),
-        f.ali,
+        Key::Account(f.ali),
         F_Sender(f.ali)
     );
     f.create_erc20(
@@ -321,7 +1438,397 @@ fn test_factory_create_redundant() {
         token_cfg::SYMBOL.to_string(),
         token_cfg::DECIMALS,
         token_cfg::total_supply(),
-        f.ali,
+        Key::Account(f.ali),
+        F_Sender(f.ali)
+    );
+}
+
+#[test]
+fn test_factory_governance_rotation() {
+    let mut f = Factory::deployed();
+    f.propose_factory_governance(Key::Account(f.bob), F_Sender(f.ali));
+    f.advance_block_time(GOVERNANCE_DELAY);
+    f.apply_factory_governance(F_Sender(f.bob));
+    assert_eq!(f.factory_governance(), Key::Account(f.bob));
+    f.create_erc20(
+        token_cfg::NAME.to_string(),
+        token_cfg::SYMBOL.to_string(),
+        token_cfg::DECIMALS,
+        token_cfg::total_supply(),
+        Key::Account(f.bob),
+        F_Sender(f.bob)
+    );
+    assert_ne!(f.get_erc20_hash(token_cfg::NAME.to_string()), ContractHash::default());
+}
+
+#[test]
+fn test_factory_apply_governance_before_delay() {
+    let mut f = Factory::deployed();
+    let (ali, bob) = (f.ali, f.bob);
+    f.propose_factory_governance(Key::Account(bob), F_Sender(ali));
+    // Error::GovernanceDelayNotPassed
+    assert_user_error(8, || f.apply_factory_governance(F_Sender(bob)));
+}
+
+#[test]
+fn test_factory_contract_governance() {
+    let mut f = Factory::deployed();
+    let ali = f.ali;
+    let (proxy, proxy_key) = f.deploy_governance_proxy(F_Sender(ali));
+    f.propose_factory_governance(proxy_key, F_Sender(ali));
+    f.advance_block_time(GOVERNANCE_DELAY);
+    f.proxy_execute(proxy, "apply_factory_governance", runtime_args! {}, F_Sender(ali));
+    assert_eq!(f.factory_governance(), proxy_key);
+    // Error::NoAccessRights, the previous account governance is out
+    assert_user_error(3, || f.create_erc20(
+        token_cfg::NAME.to_string(),
+        token_cfg::SYMBOL.to_string(),
+        token_cfg::DECIMALS,
+        token_cfg::total_supply(),
+        Key::Account(ali),
+        F_Sender(ali)
+    ));
+    f.proxy_execute(proxy, "create_erc20", runtime_args! {
+        "token_name" => token_cfg::NAME.to_string(),
+        "token_symbol" => token_cfg::SYMBOL.to_string(),
+        "token_decimals" => token_cfg::DECIMALS,
+        "token_total_supply" => token_cfg::total_supply(),
+        "token_max_supply" => None::<U256>,
+        "governance" => Key::Account(ali)
+    }, F_Sender(ali));
+    assert_ne!(f.get_erc20_hash(token_cfg::NAME.to_string()), ContractHash::default());
+}
+
+#[test]
+fn test_factory_token_roles() {
+    let mut f = Factory::deployed();
+    f.create_erc20(
+        token_cfg::NAME.to_string(),
+        token_cfg::SYMBOL.to_string(),
+        token_cfg::DECIMALS,
+        token_cfg::total_supply(),
+        Key::Account(f.ali),
+        F_Sender(f.ali)
+    );
+    assert!(f.token_has_role(token_cfg::NAME, "admin", Key::Account(f.ali)));
+    f.token_grant_role(token_cfg::NAME, "minter", Key::Account(f.bob), F_Sender(f.ali));
+    f.token_mint(token_cfg::NAME, Key::Account(f.joe), 10.into(), F_Sender(f.bob));
+    assert_eq!(f.token_balance_of(token_cfg::NAME, Key::Account(f.joe)), 10.into());
+}
+
+#[test]
+fn test_factory_token_mint_without_role() {
+    let mut f = Factory::deployed();
+    let (bob, joe) = (f.bob, f.joe);
+    f.create_erc20(
+        token_cfg::NAME.to_string(),
+        token_cfg::SYMBOL.to_string(),
+        token_cfg::DECIMALS,
+        token_cfg::total_supply(),
+        Key::Account(f.ali),
         F_Sender(f.ali)
     );
-}
\ No newline at end of file
+    // Error::NoAccessRights
+    assert_user_error(3, || f.token_mint(token_cfg::NAME, Key::Account(joe), 10.into(), F_Sender(bob)));
+}
+
+// ------------ START - Router Tests ------------
+#[test]
+fn test_any_swap_out() {
+    let mut r = Router::deployed();
+    let amount: U256 = 10.into();
+    let ali = r.ali;
+    // Error::InsufficientAllowance, the router burns through the caller's allowance
+    assert_user_error(11, || r.any_swap_out(bridge_cfg::TOKEN, TO_ADDRESS, amount, TO_CHAIN_ID.into(), R_Sender(ali)));
+    r.approve_router(amount, R_Sender(ali));
+    r.any_swap_out(bridge_cfg::TOKEN, TO_ADDRESS, amount, TO_CHAIN_ID.into(), R_Sender(ali));
+    assert_eq!(r.token_balance(to_key(r.ali)), bridge_cfg::total_supply() - amount);
+    assert_eq!(r.token_total_supply(), bridge_cfg::total_supply() - amount);
+    assert_eq!(r.event_count(), 1);
+    let event = r.event(0);
+    assert_eq!(event["event_type"], "swap_out");
+    assert_eq!(event["token"], bridge_cfg::TOKEN);
+    assert_eq!(event["from"], to_key(r.ali).to_formatted_string());
+    assert_eq!(event["to"], TO_ADDRESS);
+    assert_eq!(event["amount"], "10");
+    assert_eq!(event["to_chain_id"], TO_CHAIN_ID.to_string());
+}
+
+#[test]
+fn test_any_swap_out_unknown_token() {
+    let mut r = Router::deployed();
+    let ali = r.ali;
+    // Error::TokenNotFound, from the factory
+    assert_user_error(5, || r.any_swap_out("DAI", TO_ADDRESS, 10.into(), TO_CHAIN_ID.into(), R_Sender(ali)));
+}
+
+#[test]
+fn test_any_swap_out_too_much() {
+    let mut r = Router::deployed();
+    let bob = r.bob;
+    r.approve_router(10.into(), R_Sender(bob));
+    // Error::BurnAmountExceedsBalance
+    assert_user_error(2, || r.any_swap_out(bridge_cfg::TOKEN, TO_ADDRESS, 10.into(), TO_CHAIN_ID.into(), R_Sender(bob)));
+}
+
+#[test]
+fn test_any_swap_in() {
+    let mut r = Router::deployed();
+    let amount: U256 = 10.into();
+    r.any_swap_in(SWAP_TX_HASH, bridge_cfg::TOKEN, to_key(r.bob), amount, TO_CHAIN_ID.into(), R_Sender(r.ali));
+    assert_eq!(r.token_balance(to_key(r.bob)), amount);
+    assert_eq!(r.token_total_supply(), bridge_cfg::total_supply() + amount);
+    let event = r.event(0);
+    assert_eq!(event["event_type"], "swap_in");
+    assert_eq!(event["tx_hash"], SWAP_TX_HASH);
+    assert_eq!(event["to"], to_key(r.bob).to_formatted_string());
+    assert_eq!(event["from_chain_id"], TO_CHAIN_ID.to_string());
+}
+
+#[test]
+fn test_any_swap_in_needs_minter_role() {
+    let mut r = Router::deployed();
+    let (ali, bob) = (r.ali, r.bob);
+    let router = Key::Hash(r.hash("Router_package"));
+    r.revoke_role("minter", router, R_Sender(ali));
+    // Error::NoAccessRights, from the factory token
+    assert_user_error(3, || r.any_swap_in(SWAP_TX_HASH, bridge_cfg::TOKEN, to_key(bob), 10.into(), TO_CHAIN_ID.into(), R_Sender(ali)));
+    r.grant_role("minter", router, R_Sender(ali));
+    r.any_swap_in(SWAP_TX_HASH, bridge_cfg::TOKEN, to_key(bob), 10.into(), TO_CHAIN_ID.into(), R_Sender(ali));
+    assert_eq!(r.token_balance(to_key(bob)), 10.into());
+}
+
+#[test]
+fn test_any_swap_in_replay() {
+    let mut r = Router::deployed();
+    let (ali, bob) = (r.ali, r.bob);
+    r.any_swap_in(SWAP_TX_HASH, bridge_cfg::TOKEN, to_key(bob), 10.into(), TO_CHAIN_ID.into(), R_Sender(ali));
+    // Error::SwapInAlreadyProcessed
+    assert_user_error(1, || r.any_swap_in(SWAP_TX_HASH, bridge_cfg::TOKEN, to_key(bob), 10.into(), TO_CHAIN_ID.into(), R_Sender(ali)));
+}
+
+#[test]
+fn test_any_swap_in_unauthorized() {
+    let mut r = Router::deployed();
+    let bob = r.bob;
+    // Error::NoAccessRights
+    assert_user_error(0, || r.any_swap_in(SWAP_TX_HASH, bridge_cfg::TOKEN, to_key(bob), 10.into(), TO_CHAIN_ID.into(), R_Sender(bob)));
+}
+
+#[test]
+fn test_any_swap_native() {
+    let mut r = Router::deployed();
+    let amount: U512 = 1_000.into();
+    r.any_swap_out_native(r.main_purse(r.bob), TO_ADDRESS, amount, TO_CHAIN_ID.into(), R_Sender(r.bob));
+    assert_eq!(r.total_locked(), amount);
+    // The deposit belongs to bob, the router only relayed it.
+    assert_eq!(r.deposits_of(to_key(r.bob)), amount);
+    assert_eq!(r.deposits_of(Key::Hash(r.hash("Router_package"))), 0.into());
+    assert_eq!(r.deposit_record(0)["depositor"], to_key(r.bob).to_formatted_string());
+    let event = r.event(0);
+    assert_eq!(event["event_type"], "native_swap_out");
+    assert_eq!(event["from"], to_key(r.bob).to_formatted_string());
+    assert_eq!(event["amount"], "1000");
+
+    let target = PublicKey::ed25519_from_bytes([12u8; 32]).unwrap();
+    r.any_swap_in_native(SWAP_TX_HASH, to_key(target.to_account_hash()), amount, TO_CHAIN_ID.into(), R_Sender(r.ali));
+    assert_eq!(r.balance_of(target.to_account_hash()), amount);
+    let event = r.event(1);
+    assert_eq!(event["event_type"], "native_swap_in");
+    assert_eq!(event["to"], to_key(target.to_account_hash()).to_formatted_string());
+}
+
+#[test]
+fn test_any_swap_in_native_to_contract() {
+    let mut r = Router::deployed();
+    let (ali, bob) = (r.ali, r.bob);
+    r.any_swap_out_native(r.main_purse(bob), TO_ADDRESS, 1_000.into(), TO_CHAIN_ID.into(), R_Sender(bob));
+    let (receiver, purse) = r.deploy_purse_receiver();
+    r.any_swap_in_native(SWAP_TX_HASH, receiver, 400.into(), TO_CHAIN_ID.into(), R_Sender(ali));
+    assert_eq!(r.purse_balance(purse), 400.into());
+    assert_eq!(r.event(1)["to"], receiver.to_formatted_string());
+    r.any_swap_in_native("tx-2", Key::URef(purse), 100.into(), TO_CHAIN_ID.into(), R_Sender(ali));
+    assert_eq!(r.purse_balance(purse), 500.into());
+}
+
+#[test]
+fn test_refund_router_deposit() {
+    let mut r = Router::deployed();
+    let (ali, bob) = (r.ali, r.bob);
+    r.any_swap_out_native(r.main_purse(bob), TO_ADDRESS, 1_000.into(), TO_CHAIN_ID.into(), R_Sender(bob));
+    r.set_refund_timeout(Some(REFUND_TIMEOUT), R_Sender(ali));
+    r.advance_block_time(REFUND_TIMEOUT);
+    let balance = r.balance_of(bob);
+    r.refund(0, R_Sender(ali));
+    // The refund goes back to bob, not to the router that relayed the deposit.
+    assert_eq!(r.balance_of(bob), balance + 1_000);
+    assert_eq!(r.deposits_of(to_key(bob)), 0.into());
+    assert_eq!(r.total_locked(), 0.into());
+    assert_eq!(r.deposit_record(0)["status"], "refunded");
+    r.reconcile(R_Sender(ali));
+}
+
+#[test]
+fn test_lock_for_only_router() {
+    let mut r = Router::deployed();
+    let bob = r.bob;
+    // Error::NoAccessRights
+    assert_user_error(1, || r.lock_for(to_key(bob), 1_000.into(), R_Sender(bob)));
+}
+
+#[test]
+fn test_wcspr_wrap_and_redeem() {
+    let mut r = Router::deployed();
+    r.enable_wcspr();
+    let (ali, bob) = (r.ali, r.bob);
+    r.wrap(1_000.into(), R_Sender(ali));
+    r.wrap(500.into(), R_Sender(bob));
+    assert_eq!(r.wcspr_balance(to_key(ali)), 1_000.into());
+    assert_eq!(r.wcspr_balance(to_key(bob)), 500.into());
+    assert_eq!(r.wcspr_total_supply(), 1_500.into());
+    assert_eq!(r.holder_purse_balance(), 1_500.into());
+
+    r.approve_holder(400.into(), R_Sender(ali));
+    r.redeem(400.into(), R_Sender(ali));
+    assert_eq!(r.wcspr_balance(to_key(ali)), 600.into());
+    assert_eq!(r.wcspr_total_supply(), 1_100.into());
+    assert_eq!(r.holder_purse_balance(), 1_100.into());
+    r.reconcile(R_Sender(bob));
+    r.approve_holder(501.into(), R_Sender(bob));
+    // Error::BurnAmountExceedsBalance, from the token
+    assert_user_error(2, || r.redeem(501.into(), R_Sender(bob)));
+}
+
+#[test]
+fn test_wcspr_redeem_needs_only_allowance() {
+    let mut r = Router::deployed();
+    r.enable_wcspr();
+    let ali = r.ali;
+    r.wrap(1_000.into(), R_Sender(ali));
+    // Error::InsufficientAllowance, from the token: `redeem` burns through `burn_from`
+    assert_user_error(11, || r.redeem(400.into(), R_Sender(ali)));
+    // WCSPR governance switching forced burns off can't lock holders out of their CSPR.
+    r.disable_wcspr_forced_burn(R_Sender(ali));
+    r.approve_holder(400.into(), R_Sender(ali));
+    r.redeem(400.into(), R_Sender(ali));
+    assert_eq!(r.wcspr_balance(to_key(ali)), 600.into());
+    assert_eq!(r.holder_purse_balance(), 600.into());
+    r.reconcile(R_Sender(ali));
+}
+
+#[test]
+fn test_wcspr_disables_bridge() {
+    let mut r = Router::deployed();
+    r.enable_wcspr();
+    let (ali, bob) = (r.ali, r.bob);
+    r.wrap(1_000.into(), R_Sender(ali));
+    // Error::LockDisabledInWrappedMode, directly and through the router
+    assert_user_error(27, || r.lock(1_000.into(), R_Sender(ali)));
+    let src_purse = r.main_purse(bob);
+    assert_user_error(27, || r.any_swap_out_native(src_purse, TO_ADDRESS, 100.into(), TO_CHAIN_ID.into(), R_Sender(bob)));
+    let target = to_key(PublicKey::ed25519_from_bytes([12u8; 32]).unwrap().to_account_hash());
+    // Error::UnlockDisabledInWrappedMode
+    assert_user_error(21, || r.any_swap_in_native(SWAP_TX_HASH, target, 1_000.into(), TO_CHAIN_ID.into(), R_Sender(ali)));
+    // Error::WcsprAlreadySet
+    let wcspr = Key::Hash(r.wcspr_hash());
+    assert_user_error(19, || r.set_wcspr(wcspr, R_Sender(ali)));
+}
+
+#[test]
+fn test_wcspr_waits_for_pending_deposits() {
+    let mut r = Router::deployed();
+    let ali = r.ali;
+    r.lock(1_000.into(), R_Sender(ali));
+    // Error::DepositsPending, a later refund would pay out CSPR no WCSPR backs
+    assert_user_error(28, || r.enable_wcspr());
+    r.set_refund_timeout(Some(REFUND_TIMEOUT), R_Sender(ali));
+    r.advance_block_time(REFUND_TIMEOUT);
+    r.refund(0, R_Sender(ali));
+    let wcspr = Key::Hash(r.wcspr_hash());
+    r.set_wcspr(wcspr, R_Sender(ali));
+    r.wrap(1_000.into(), R_Sender(ali));
+    r.reconcile(R_Sender(ali));
+}
+
+#[test]
+fn test_wcspr_setup_checks() {
+    let mut r = Router::deployed();
+    let ali = r.ali;
+    // Error::WcsprNotSet
+    assert_user_error(22, || r.wrap(1.into(), R_Sender(ali)));
+    assert_user_error(22, || r.redeem(1.into(), R_Sender(ali)));
+    let weth = Key::Hash(r.token_hash());
+    // Error::InvalidWcsprDecimals, the bridged token has 18 decimals
+    assert_user_error(20, || r.set_wcspr(weth, R_Sender(ali)));
+}
+
+// ------------ START - ERC20 Vault Tests ------------
+#[test]
+fn test_vault_deposit_and_withdraw() {
+    let mut v = Vault::deployed();
+    let (ali, bob) = (v.ali, v.bob);
+    let (token, vault) = (v.token(), v.vault());
+    v.allow_token(token, V_Sender(ali));
+    // Error::InsufficientAllowance, from the token
+    assert_user_error(9, || v.deposit(token, 600.into(), TO_CHAIN_ID.into(), TO_ADDRESS, V_Sender(ali)));
+    v.approve(vault, 600.into(), V_Sender(ali));
+    v.deposit(token, 600.into(), TO_CHAIN_ID.into(), TO_ADDRESS, V_Sender(ali));
+    assert_eq!(v.token_balance(to_key(ali)), 400.into());
+    assert_eq!(v.token_balance(vault), 600.into());
+    assert_eq!(v.locked(token), 600.into());
+    assert_eq!(v.deposit_count(), 1);
+    let deposit = v.deposit_record(0);
+    assert_eq!(deposit["depositor"], to_key(ali).to_formatted_string());
+    assert_eq!(deposit["token"], token.to_formatted_string());
+    assert_eq!(deposit["amount"], "600");
+    assert_eq!(deposit["to_chain_id"], TO_CHAIN_ID.to_string());
+    assert_eq!(deposit["to_address"], TO_ADDRESS);
+    let event = v.event(1);
+    assert_eq!(event["event_type"], "deposit");
+    assert_eq!(event["token"], token.to_formatted_string());
+    assert_eq!(event["depositor"], to_key(ali).to_formatted_string());
+    assert_eq!(event["amount"], "600");
+    assert_eq!(event["to_chain_id"], TO_CHAIN_ID.to_string());
+    assert_eq!(event["to_address"], TO_ADDRESS);
+
+    v.withdraw(SWAP_TX_HASH, token, to_key(bob), 250.into(), V_Sender(ali));
+    assert_eq!(v.token_balance(to_key(bob)), 250.into());
+    assert_eq!(v.locked(token), 350.into());
+    assert_eq!(v.event(2)["event_type"], "withdrawal");
+    // Error::WithdrawalAlreadyProcessed
+    assert_user_error(2, || v.withdraw(SWAP_TX_HASH, token, to_key(bob), 250.into(), V_Sender(ali)));
+    // Error::InsufficientVaultBalance
+    assert_user_error(4, || v.withdraw("tx-2", token, to_key(bob), 351.into(), V_Sender(ali)));
+}
+
+#[test]
+fn test_vault_token_allowlist() {
+    let mut v = Vault::deployed();
+    let (ali, bob) = (v.ali, v.bob);
+    let (token, vault) = (v.token(), v.vault());
+    v.approve(vault, 600.into(), V_Sender(ali));
+    // Error::TokenNotAllowed
+    assert_user_error(3, || v.deposit(token, 100.into(), TO_CHAIN_ID.into(), TO_ADDRESS, V_Sender(ali)));
+    // Error::NoAccessRights
+    assert_user_error(1, || v.allow_token(token, V_Sender(bob)));
+    v.allow_token(token, V_Sender(ali));
+    v.deposit(token, 100.into(), TO_CHAIN_ID.into(), TO_ADDRESS, V_Sender(ali));
+    v.disallow_token(token, V_Sender(ali));
+    // Error::TokenNotAllowed
+    assert_user_error(3, || v.deposit(token, 100.into(), TO_CHAIN_ID.into(), TO_ADDRESS, V_Sender(ali)));
+    // Delisting only stops deposits, what is locked can still be released.
+    v.withdraw(SWAP_TX_HASH, token, to_key(bob), 100.into(), V_Sender(ali));
+    assert_eq!(v.locked(token), 0.into());
+}
+
+#[test]
+fn test_vault_withdraw_unauthorized() {
+    let mut v = Vault::deployed();
+    let (ali, bob) = (v.ali, v.bob);
+    let (token, vault) = (v.token(), v.vault());
+    v.allow_token(token, V_Sender(ali));
+    v.approve(vault, 100.into(), V_Sender(ali));
+    v.deposit(token, 100.into(), TO_CHAIN_ID.into(), TO_ADDRESS, V_Sender(ali));
+    // Error::NoAccessRights
+    assert_user_error(1, || v.withdraw(SWAP_TX_HASH, token, to_key(bob), 100.into(), V_Sender(bob)));
+}